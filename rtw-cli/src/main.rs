@@ -8,6 +8,12 @@ struct Args {
     multi_threaded: bool,
     #[arg(long, short)]
     scene: String,
+    /// Number of frames to render for animated scenes
+    #[arg(long, short)]
+    frames: Option<u32>,
+    /// Trace wavelengths instead of RGB, for dispersive glass
+    #[arg(long)]
+    spectral: bool,
 }
 
 impl From<Args> for RenderConfig {
@@ -15,6 +21,8 @@ impl From<Args> for RenderConfig {
         Self {
             multi_threaded: value.multi_threaded,
             output_file: value.output,
+            frames: value.frames,
+            spectral: value.spectral,
         }
     }
 }
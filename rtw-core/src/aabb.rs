@@ -1,6 +1,6 @@
 use crate::{
     ray::Ray,
-    utils::interval::{Interval, IntervalExtend, New},
+    utils::interval::{Interval, IntervalExtend, New, Pad},
     Result,
 };
 use glam::DVec3;
@@ -49,6 +49,11 @@ impl Aabb {
         )
     }
 
+    /// Grows every axis by `delta` on both sides
+    pub fn pad(&self, delta: f64) -> Self {
+        Self::new(self.x.pad(delta), self.y.pad(delta), self.z.pad(delta))
+    }
+
     pub fn interval(&self, axis: Axis) -> Interval {
         match axis {
             Axis::X => self.x.clone(),
@@ -57,6 +62,16 @@ impl Aabb {
         }
     }
 
+    pub fn corners(&self) -> [DVec3; 8] {
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|i| {
+            DVec3::new(
+                if i & 1 == 0 { self.x.start } else { self.x.end },
+                if i & 2 == 0 { self.y.start } else { self.y.end },
+                if i & 4 == 0 { self.z.start } else { self.z.end },
+            )
+        })
+    }
+
     pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<Interval> {
         let mut t_start = interval.start;
         let mut t_end = interval.end;
@@ -0,0 +1,142 @@
+use std::path::Path;
+
+use crate::{
+    RenderConfig, Result, RtwError,
+    camera::{CameraPose, builder::CameraBuilder},
+    hittable::Hittable,
+    utils::keyframe::{Interpolate, Keyframes},
+};
+
+/// Camera settings that can change from one frame to the next
+#[derive(Debug, Clone, Copy)]
+pub struct CameraKey {
+    pub pose: CameraPose,
+    pub vfov: f64,
+    pub focus_distance: f64,
+}
+
+impl Interpolate for CameraKey {
+    fn interpolate(a: Self, b: Self, t: f64) -> Self {
+        Self {
+            pose: CameraPose::interpolate(a.pose, b.pose, t),
+            vfov: f64::interpolate(a.vfov, b.vfov, t),
+            focus_distance: f64::interpolate(a.focus_distance, b.focus_distance, t),
+        }
+    }
+
+    fn spline(p0: Self, p1: Self, p2: Self, p3: Self, t: f64) -> Self {
+        Self {
+            pose: CameraPose::spline(p0.pose, p1.pose, p2.pose, p3.pose, t),
+            vfov: f64::spline(p0.vfov, p1.vfov, p2.vfov, p3.vfov, t),
+            focus_distance: f64::spline(
+                p0.focus_distance,
+                p1.focus_distance,
+                p2.focus_distance,
+                p3.focus_distance,
+                t,
+            ),
+        }
+    }
+}
+
+/// Sequence of frames rendered along a keyframed camera path.
+///
+/// Frames are spread evenly from the first to the last keyframe time, so
+/// the first and last frames sit exactly on the end keyframes.
+pub struct Animation {
+    frames: u32,
+    path: Keyframes<CameraKey>,
+    /// Fraction of a frame the shutter stays open, in degrees
+    shutter_angle: f64,
+}
+
+impl Animation {
+    pub fn new(frames: u32, path: Keyframes<CameraKey>) -> Self {
+        Self {
+            frames,
+            path,
+            shutter_angle: 0.0,
+        }
+    }
+
+    /// Opens the shutter for `angle / 360` of the time between two frames,
+    /// starting at the frame time. 180 degrees is the usual film look, 0
+    /// renders each frame at a single instant without motion blur.
+    pub fn shutter_angle(mut self, angle: f64) -> Self {
+        self.shutter_angle = angle;
+        self
+    }
+
+    /// Scene time between two consecutive frames
+    pub fn frame_duration(&self) -> f64 {
+        let start = self.path.times().next().unwrap_or(0.0);
+        let end = self.path.times().last().unwrap_or(start);
+        if self.frames <= 1 {
+            0.0
+        } else {
+            (end - start) / (self.frames - 1) as f64
+        }
+    }
+
+    /// Scene time of the given frame
+    pub fn frame_time(&self, frame: u32) -> f64 {
+        let start = self.path.times().next().unwrap_or(0.0);
+        start + self.frame_duration() * frame as f64
+    }
+
+    /// Renders every frame to `config.output_file` with a frame number
+    /// appended, `out.png` becoming `out_0001.png`, `out_0002.png`, ...
+    ///
+    /// Each frame builds a new camera from `camera`, replacing its placement,
+    /// field of view and focus distance with the ones on the path. A camera
+    /// with pose keyframes of its own is rejected, as those would override
+    /// the placement on the path.
+    pub fn render<T>(&self, camera: &CameraBuilder, world: &T, config: &RenderConfig) -> Result<()>
+    where
+        T: Hittable,
+    {
+        if camera.has_pose_keyframes() {
+            return Err(RtwError::ConflictingCameraPath);
+        }
+
+        for frame in 0..self.frames {
+            let time = self.frame_time(frame);
+            let key = self.path.sample(time);
+            let exposure = self.frame_duration() * self.shutter_angle / 360.0;
+
+            let frame_camera = camera
+                .clone()
+                .look_from(key.pose.look_from)
+                .look_at(key.pose.look_at)
+                .vfov(key.vfov)
+                .defocus_distance(key.focus_distance)
+                .shutter(time, time + exposure)
+                .build();
+
+            let frame_config = RenderConfig {
+                multi_threaded: config.multi_threaded,
+                output_file: numbered_file(&config.output_file, frame + 1),
+                frames: None,
+                spectral: config.spectral,
+            };
+            frame_camera.render(world, &frame_config)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn numbered_file(output_file: &str, frame: u32) -> String {
+    let path = Path::new(output_file);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(ext) => format!("{stem}_{frame:04}.{}", ext.to_string_lossy()),
+        None => format!("{stem}_{frame:04}"),
+    };
+    path.with_file_name(file_name)
+        .to_string_lossy()
+        .into_owned()
+}
@@ -0,0 +1,50 @@
+pub mod environment;
+pub mod sun_sky;
+
+use glam::DVec3;
+use std::sync::Arc;
+
+use crate::{
+    background::{environment::EnvironmentMap, sun_sky::SunSky},
+    pdf::Pdf,
+    ray::Ray,
+};
+
+/// Radiance seen by rays leaving the scene
+#[derive(Debug, Clone, Default)]
+pub enum Background {
+    /// White at the horizon blending to blue straight up
+    #[default]
+    Sky,
+    /// Same color in every direction, black for scenes lit only by objects
+    Solid(DVec3),
+    /// Image of the surroundings, lighting the scene as they would
+    Environment(Arc<EnvironmentMap>),
+    /// Daylight for a sun position, with the sun disk as a light of its own
+    SunSky(Arc<SunSky>),
+}
+
+impl Background {
+    pub fn value(&self, ray: &Ray) -> DVec3 {
+        match self {
+            Background::Sky => {
+                let unit_dir = ray.direction.normalize();
+                let a = 0.5 * (unit_dir.y + 1.0);
+                DVec3::lerp(DVec3::ONE, DVec3::new(0.5, 0.7, 1.0), a)
+            }
+            Background::Solid(color) => *color,
+            Background::Environment(map) => map.radiance(ray.direction),
+            Background::SunSky(sun_sky) => sun_sky.radiance(ray.direction),
+        }
+    }
+
+    /// Density of directions towards the background for direct light
+    /// sampling, None for backgrounds even enough to be found by chance
+    pub fn pdf(&self) -> Option<&dyn Pdf> {
+        match self {
+            Background::Sky | Background::Solid(_) => None,
+            Background::Environment(map) => Some(map.as_ref()),
+            Background::SunSky(sun_sky) => Some(sun_sky.as_ref()),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use glam::{DQuat, DVec3};
+use rand::Rng;
+use std::f64::consts::PI;
+
+use crate::{Result, RtwError, pdf::Pdf};
+
+/// Light arriving from every direction, stored as an equirectangular image:
+/// `u` wraps around the y axis starting from -x like the (u, v) coordinates
+/// of a sphere, and rows run from straight up to straight down.
+///
+/// As a [`Pdf`] it picks directions in proportion to the luminance of the
+/// map, so bright areas such as the sun in a sky probe are found by direct
+/// light sampling instead of by chance.
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    /// Radiance, row major starting at the top left
+    texels: Vec<DVec3>,
+    /// Turn of the map about the y axis
+    rotation: DQuat,
+    /// Factor on the stored radiance
+    intensity: f64,
+    /// Running total of the sampling weight of the texels along each row,
+    /// `width + 1` entries per row starting at 0
+    columns: Vec<f64>,
+    /// Running total of the sampling weight of whole rows, `height + 1`
+    /// entries starting at 0
+    rows: Vec<f64>,
+}
+
+impl EnvironmentMap {
+    /// Map of `width` by `height` texels of radiance, row major starting
+    /// at the top left
+    pub fn new(width: usize, height: usize, texels: Vec<DVec3>) -> Result<Self> {
+        if width == 0 || height == 0 || texels.len() != width * height {
+            return Err(RtwError::InvalidEnvironmentMap(width, height, texels.len()));
+        }
+
+        // Rows near the poles are squeezed into smaller solid angles, which
+        // their weight accounts for
+        let weights = |luminance: &dyn Fn(DVec3) -> f64| {
+            let mut columns = Vec::with_capacity(height * (width + 1));
+            let mut rows = vec![0.0];
+            for (y, row) in texels.chunks_exact(width).enumerate() {
+                let sin_theta = (PI * (y as f64 + 0.5) / height as f64).sin();
+                let mut total = 0.0;
+                columns.push(total);
+                for &texel in row {
+                    total += luminance(texel) * sin_theta;
+                    columns.push(total);
+                }
+                rows.push(rows[y] + total);
+            }
+            (columns, rows)
+        };
+        let (mut columns, mut rows) =
+            weights(&|c: DVec3| c.dot(DVec3::new(0.2126, 0.7152, 0.0722)).max(0.0));
+        // A black map is sampled uniformly over the sphere instead
+        if rows[height] <= 0.0 {
+            (columns, rows) = weights(&|_| 1.0);
+        }
+
+        Ok(Self {
+            width,
+            height,
+            texels,
+            rotation: DQuat::IDENTITY,
+            intensity: 1.0,
+            columns,
+            rows,
+        })
+    }
+
+    /// Reads a Radiance `.hdr` file, or any other image the image crate
+    /// reads as linear floats
+    pub fn open(path: &str) -> Result<Self> {
+        let img = image::open(path)
+            .map_err(|err| RtwError::ImageError(err.to_string()))?
+            .into_rgb32f();
+        let texels = img
+            .pixels()
+            .map(|p| DVec3::new(p.0[0] as f64, p.0[1] as f64, p.0[2] as f64))
+            .collect();
+        Self::new(img.width() as usize, img.height() as usize, texels)
+    }
+
+    /// Turns the map about the y axis by `angle` in radians
+    pub fn rotation(mut self, angle: f64) -> Self {
+        self.rotation = DQuat::from_rotation_y(angle);
+        self
+    }
+
+    pub fn intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Radiance arriving from `direction`. Texels are not blended, so the
+    /// radiance is constant wherever the sampling density is and a bright
+    /// texel never bleeds into a neighbour that is rarely sampled.
+    pub fn radiance(&self, direction: DVec3) -> DVec3 {
+        let (x, y) = self.texel(direction);
+        self.intensity * self.texels[y * self.width + x]
+    }
+
+    /// Column and row of the texel seen in `direction`
+    fn texel(&self, direction: DVec3) -> (usize, usize) {
+        let (u, v) = self.uv(direction);
+        let x = ((u * self.width as f64) as usize).min(self.width - 1);
+        let y = ((v * self.height as f64) as usize).min(self.height - 1);
+        (x, y)
+    }
+
+    /// Map coordinates in [0, 1] of a direction in the scene
+    fn uv(&self, direction: DVec3) -> (f64, f64) {
+        let d = (self.rotation.inverse() * direction).normalize();
+        let theta = d.y.clamp(-1.0, 1.0).acos();
+        let phi = f64::atan2(-d.z, d.x) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+
+    /// Direction in the scene of the map coordinates (u, v)
+    fn direction(&self, u: f64, v: f64) -> DVec3 {
+        let (sin_theta, cos_theta) = (PI * v).sin_cos();
+        let (sin_phi, cos_phi) = (2.0 * PI * u - PI).sin_cos();
+        self.rotation * DVec3::new(sin_theta * cos_phi, cos_theta, -sin_theta * sin_phi)
+    }
+}
+
+/// Index of the entry whose span of `running` totals holds `target`
+fn find(running: &[f64], target: f64) -> usize {
+    running
+        .partition_point(|&total| total <= target)
+        .saturating_sub(1)
+        .min(running.len() - 2)
+}
+
+impl Pdf for EnvironmentMap {
+    fn value(&self, direction: DVec3) -> f64 {
+        let (x, y) = self.texel(direction);
+        let row = y * (self.width + 1);
+        let weight = self.columns[row + x + 1] - self.columns[row + x];
+
+        // Density over the map, spread over the solid angle it maps to
+        let density = weight / self.rows[self.height] * (self.width * self.height) as f64;
+        let sin_theta = (1.0 - direction.normalize().y.powi(2)).max(0.0).sqrt();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        density / (2.0 * PI * PI * sin_theta)
+    }
+
+    fn generate(&self) -> DVec3 {
+        let mut rng = rand::rng();
+        let y = find(&self.rows, rng.random::<f64>() * self.rows[self.height]);
+        let row = &self.columns[y * (self.width + 1)..(y + 1) * (self.width + 1)];
+        let x = find(row, rng.random::<f64>() * row[self.width]);
+
+        let u = (x as f64 + rng.random::<f64>()) / self.width as f64;
+        let v = (y as f64 + rng.random::<f64>()) / self.height as f64;
+        self.direction(u, v)
+    }
+}
+
+impl std::fmt::Debug for EnvironmentMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnvironmentMap")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("rotation", &self.rotation)
+            .field("intensity", &self.intensity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dim map with one bright texel, turned so texels and the
+    /// integration grid don't line up
+    fn spot_map() -> EnvironmentMap {
+        let mut texels = vec![DVec3::splat(0.1); 8 * 4];
+        texels[8 + 5] = DVec3::new(60.0, 50.0, 40.0);
+        EnvironmentMap::new(8, 4, texels).unwrap().rotation(0.3)
+    }
+
+    /// Integral of the density over the directions accepted by `keep`,
+    /// with the midpoint rule over theta and phi
+    fn integral(map: &EnvironmentMap, keep: impl Fn(DVec3) -> bool) -> f64 {
+        let (n_theta, n_phi) = (400, 800);
+        let (d_theta, d_phi) = (PI / n_theta as f64, 2.0 * PI / n_phi as f64);
+        let mut total = 0.0;
+        for i in 0..n_theta {
+            let theta = (i as f64 + 0.5) * d_theta;
+            for j in 0..n_phi {
+                let phi = (j as f64 + 0.5) * d_phi;
+                let direction = DVec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                if keep(direction) {
+                    total += map.value(direction) * theta.sin() * d_theta * d_phi;
+                }
+            }
+        }
+        total
+    }
+
+    #[test]
+    fn density_integrates_to_one() {
+        let total = integral(&spot_map(), |_| true);
+        assert!((total - 1.0).abs() < 1e-2, "{total}");
+    }
+
+    #[test]
+    fn samples_follow_density() {
+        let map = spot_map();
+        let in_spot = |direction: DVec3| map.texel(direction) == (5, 1);
+        let expected = integral(&map, in_spot);
+
+        let samples = 100_000;
+        let hits = (0..samples).filter(|_| in_spot(map.generate())).count();
+        let fraction = hits as f64 / samples as f64;
+        assert!(
+            (fraction - expected).abs() < 1e-2,
+            "{fraction} vs {expected}"
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use glam::DVec3;
+use rand::Rng;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::{
+    background::environment::EnvironmentMap, pdf::Pdf, spectrum::xyz_to_srgb, utils::onb::Onb,
+};
+
+/// Radiance per kcd/m² of luminance, putting a white diffuse surface in
+/// midday sun near 1
+const LUMINANCE_SCALE: f64 = 0.02;
+
+/// Luminance of the sun disk above the atmosphere, in kcd/m²
+const SUN_LUMINANCE: f64 = 2.0e6;
+
+/// Angular radius of the sun disk
+const SUN_RADIUS: f64 = 0.00465;
+
+/// Resolution of the table the sky is sampled from
+const TABLE_WIDTH: usize = 256;
+const TABLE_HEIGHT: usize = 128;
+
+/// Clear daylight sky from Preetham et al. 1999 "A Practical Analytic Model
+/// for Daylight", with the sun as a disk of its own.
+///
+/// The scene is oriented with y up, north along -z and east along +x. The
+/// ground below the horizon is a diffuse plane of `ground_albedo` lit by
+/// the sun and sky. Directions are sampled towards the sun disk or the sky
+/// in proportion to the light they bring.
+pub struct SunSky {
+    /// Towards the center of the sun
+    sun_direction: DVec3,
+    /// Haziness of the air, from 2 for a very clear sky to 10 for haze
+    turbidity: f64,
+    ground_albedo: DVec3,
+    /// Factor on all radiance
+    intensity: f64,
+    /// Radiance of the sun disk, reddened by the air it crosses
+    sun_radiance: DVec3,
+    /// Radiance of the ground
+    ground_radiance: DVec3,
+    /// Perez coefficients of luminance and the x and y chromaticities
+    perez: [[f64; 5]; 3],
+    /// Values of luminance and chromaticities at the zenith
+    zenith: DVec3,
+    /// Sky and ground tabulated for sampling, without the sun
+    table: EnvironmentMap,
+    /// Chance of sampling the sun disk rather than the table
+    sun_weight: f64,
+}
+
+impl SunSky {
+    pub fn new(sun_direction: DVec3) -> Self {
+        Self {
+            sun_direction: sun_direction.normalize(),
+            turbidity: 3.0,
+            ground_albedo: DVec3::splat(0.3),
+            intensity: 1.0,
+            sun_radiance: DVec3::ZERO,
+            ground_radiance: DVec3::ZERO,
+            perez: [[0.0; 5]; 3],
+            zenith: DVec3::ZERO,
+            table: EnvironmentMap::new(1, 1, vec![DVec3::ZERO]).expect("one texel map"),
+            sun_weight: 0.0,
+        }
+        .update()
+    }
+
+    /// Sun at a place and time, from the solar position formulas in the
+    /// appendix of Preetham et al. `latitude` and `longitude` are in
+    /// degrees with north and east positive, `hour` is universal time.
+    pub fn from_time(latitude: f64, longitude: f64, day_of_year: u32, hour: f64) -> Self {
+        let day = day_of_year as f64;
+        let latitude = latitude.to_radians();
+        // Local solar time from the equation of time
+        let solar_time = hour + longitude / 15.0 + 0.170 * (4.0 * PI * (day - 80.0) / 373.0).sin()
+            - 0.129 * (2.0 * PI * (day - 8.0) / 355.0).sin();
+        let declination = 0.4093 * (2.0 * PI * (day - 81.0) / 368.0).sin();
+
+        let hour_angle = PI * solar_time / 12.0;
+        let elevation = (latitude.sin() * declination.sin()
+            - latitude.cos() * declination.cos() * hour_angle.cos())
+        .asin();
+        // Azimuth from south, positive towards west
+        let azimuth = f64::atan2(
+            -declination.cos() * hour_angle.sin(),
+            latitude.cos() * declination.sin()
+                - latitude.sin() * declination.cos() * hour_angle.cos(),
+        );
+
+        let south = DVec3::Z;
+        let west = -DVec3::X;
+        let horizontal = azimuth.cos() * south + azimuth.sin() * west;
+        Self::new(elevation.cos() * horizontal + elevation.sin() * DVec3::Y)
+    }
+
+    pub fn turbidity(mut self, turbidity: f64) -> Self {
+        self.turbidity = turbidity.clamp(2.0, 10.0);
+        self.update()
+    }
+
+    pub fn ground_albedo(mut self, ground_albedo: DVec3) -> Self {
+        self.ground_albedo = ground_albedo;
+        self.update()
+    }
+
+    pub fn intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Radiance arriving from `direction`
+    pub fn radiance(&self, direction: DVec3) -> DVec3 {
+        let direction = direction.normalize();
+        let sky = if direction.y > 0.0 {
+            self.sky(direction)
+        } else {
+            self.ground_radiance
+        };
+        let sun = if self.in_sun(direction) {
+            self.sun_radiance
+        } else {
+            DVec3::ZERO
+        };
+        self.intensity * (sky + sun)
+    }
+
+    /// Whether `direction` points into the sun disk above the horizon
+    fn in_sun(&self, direction: DVec3) -> bool {
+        self.sun_direction.y > 0.0 && direction.dot(self.sun_direction) >= SUN_RADIUS.cos()
+    }
+
+    /// Sky radiance from the Perez distributions of luminance and
+    /// chromaticity, scaled to match their values at the zenith
+    fn sky(&self, direction: DVec3) -> DVec3 {
+        let theta_sun = self.sun_direction.y.clamp(0.0, 1.0).acos();
+        let cos_theta = direction.y.max(1e-3);
+        let gamma = direction.dot(self.sun_direction).clamp(-1.0, 1.0).acos();
+
+        let [luminance, x, y] = std::array::from_fn(|i| {
+            self.zenith[i] * perez(self.perez[i], cos_theta, gamma)
+                / perez(self.perez[i], 1.0, theta_sun)
+        });
+        LUMINANCE_SCALE * xyz_to_srgb(DVec3::new(x / y, 1.0, (1.0 - x - y) / y) * luminance)
+    }
+
+    /// Recomputes everything that depends on the sun and the air
+    fn update(mut self) -> Self {
+        let t = self.turbidity;
+        let theta_sun = self.sun_direction.y.clamp(0.0, 1.0).acos();
+
+        self.perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_sun);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let chromaticity = |c: [[f64; 4]; 3]| {
+            let cubic = |k: [f64; 4]| {
+                k[0] * theta_sun.powi(3) + k[1] * theta_sun.powi(2) + k[2] * theta_sun + k[3]
+            };
+            t * t * cubic(c[0]) + t * cubic(c[1]) + cubic(c[2])
+        };
+        let zenith_x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let zenith_y = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+        self.zenith = DVec3::new(zenith_luminance.max(0.0), zenith_x, zenith_y);
+
+        self.sun_radiance = if self.sun_direction.y > 0.0 {
+            LUMINANCE_SCALE * SUN_LUMINANCE * transmittance(theta_sun, t)
+        } else {
+            DVec3::ZERO
+        };
+
+        // Light falling on the ground from the sun and the upper half of
+        // the table, then reflected evenly
+        let sun_solid_angle = 2.0 * PI * (1.0 - SUN_RADIUS.cos());
+        let sun_irradiance = self.sun_radiance * sun_solid_angle * self.sun_direction.y.max(0.0);
+        let mut sky_irradiance = DVec3::ZERO;
+        let mut texels = Vec::with_capacity(TABLE_WIDTH * TABLE_HEIGHT);
+        for row in 0..TABLE_HEIGHT {
+            let theta = PI * (row as f64 + 0.5) / TABLE_HEIGHT as f64;
+            let texel_solid_angle =
+                2.0 * PI * PI * theta.sin() / (TABLE_WIDTH * TABLE_HEIGHT) as f64;
+            for column in 0..TABLE_WIDTH {
+                let phi = 2.0 * PI * (column as f64 + 0.5) / TABLE_WIDTH as f64 - PI;
+                let direction = DVec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    -theta.sin() * phi.sin(),
+                );
+                if theta < FRAC_PI_2 {
+                    let sky = self.sky(direction);
+                    sky_irradiance += sky * texel_solid_angle * theta.cos();
+                    texels.push(sky);
+                } else {
+                    texels.push(DVec3::ZERO);
+                }
+            }
+        }
+        self.ground_radiance = self.ground_albedo * (sun_irradiance + sky_irradiance) / PI;
+        for texel in &mut texels[TABLE_WIDTH * TABLE_HEIGHT / 2..] {
+            *texel = self.ground_radiance;
+        }
+        self.table =
+            EnvironmentMap::new(TABLE_WIDTH, TABLE_HEIGHT, texels).expect("table matches its size");
+
+        // Share of the light on the ground that comes from the sun, keeping
+        // some samples on each side
+        let luminance = |c: DVec3| c.dot(DVec3::new(0.2126, 0.7152, 0.0722));
+        let sun = luminance(sun_irradiance);
+        self.sun_weight = if sun > 0.0 {
+            (sun / (sun + luminance(sky_irradiance))).clamp(0.1, 0.9)
+        } else {
+            0.0
+        };
+        self
+    }
+}
+
+/// Perez et al. 1993 sky distribution with coefficients A to E, at a
+/// direction `theta` from the zenith and `gamma` from the sun
+fn perez([a, b, c, d, e]: [f64; 5], cos_theta: f64, gamma: f64) -> f64 {
+    (1.0 + a * (b / cos_theta).exp()) * (1.0 + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+}
+
+/// Fraction of sunlight at the red, green and blue wavelengths left after
+/// Rayleigh scattering and scattering off aerosols, for a sun `theta_sun`
+/// from the zenith. Ozone and water vapour absorption are left out.
+fn transmittance(theta_sun: f64, turbidity: f64) -> DVec3 {
+    // Relative length of the path through the air, from Kasten 1966
+    let degrees = theta_sun.to_degrees().min(93.0);
+    let air_mass = 1.0 / (theta_sun.cos() + 0.15 * (93.885 - degrees).powf(-1.253));
+
+    let beta = 0.04608 * turbidity - 0.04586;
+    let wavelengths = DVec3::new(0.65, 0.57, 0.475);
+    let transmittance = |lambda: f64| {
+        let rayleigh = (-0.008735 * lambda.powf(-4.08) * air_mass).exp();
+        let aerosol = (-beta * lambda.powf(-1.3) * air_mass).exp();
+        rayleigh * aerosol
+    };
+    DVec3::new(
+        transmittance(wavelengths.x),
+        transmittance(wavelengths.y),
+        transmittance(wavelengths.z),
+    )
+}
+
+impl Pdf for SunSky {
+    fn value(&self, direction: DVec3) -> f64 {
+        let cone = 2.0 * PI * (1.0 - SUN_RADIUS.cos());
+        let sun = if self.in_sun(direction.normalize()) {
+            cone.recip()
+        } else {
+            0.0
+        };
+        self.sun_weight * sun + (1.0 - self.sun_weight) * self.table.value(direction)
+    }
+
+    fn generate(&self) -> DVec3 {
+        let mut rng = rand::rng();
+        if rng.random::<f64>() >= self.sun_weight {
+            return self.table.generate();
+        }
+        // Uniform over the cone of the disk
+        let cos_theta = 1.0 - rng.random::<f64>() * (1.0 - SUN_RADIUS.cos());
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let phi = 2.0 * PI * rng.random::<f64>();
+        Onb::new(self.sun_direction).transform(DVec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ))
+    }
+}
+
+impl std::fmt::Debug for SunSky {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SunSky")
+            .field("sun_direction", &self.sun_direction)
+            .field("turbidity", &self.turbidity)
+            .field("ground_albedo", &self.ground_albedo)
+            .field("intensity", &self.intensity)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,4 +1,4 @@
-mod builder;
+pub(crate) mod builder;
 
 use glam::DVec3;
 use indicatif::{ProgressBar, ProgressIterator, ProgressStyle};
@@ -6,25 +6,79 @@ use itertools::Itertools;
 use rand::Rng;
 use std::{
     fmt::Display,
+    path::Path,
     sync::{atomic::AtomicU64, Arc},
 };
 
 use crate::{
+    background::Background,
     camera::builder::CameraBuilder,
     hittable::Hittable,
-    ray::Ray,
-    utils::{gamma::linear_to_gamma, vec::random_in_unit_disk},
+    ray::{Ray, RayDifferentials},
+    spectrum::SampledWavelengths,
+    utils::{
+        gamma::linear_to_gamma,
+        interval::Interval,
+        keyframe::{Interpolate, Keyframes},
+        vec::random_in_unit_disk,
+    },
     RenderConfig, Result, RtwError,
 };
 
-#[allow(dead_code)]
-pub struct Camera {
-    /// Ratio of image width over image height
-    aspect_ratio: f64,
-    /// Rendered image width in pixel count
-    image_width: u32,
-    /// Calculated image height
-    image_height: u32,
+/// Where the camera is and what it looks at
+#[derive(Debug, Clone, Copy)]
+pub struct CameraPose {
+    pub look_from: DVec3,
+    pub look_at: DVec3,
+}
+
+impl Interpolate for CameraPose {
+    fn interpolate(a: Self, b: Self, t: f64) -> Self {
+        Self {
+            look_from: DVec3::interpolate(a.look_from, b.look_from, t),
+            look_at: DVec3::interpolate(a.look_at, b.look_at, t),
+        }
+    }
+
+    fn spline(p0: Self, p1: Self, p2: Self, p3: Self, t: f64) -> Self {
+        Self {
+            look_from: DVec3::spline(p0.look_from, p1.look_from, p2.look_from, p3.look_from, t),
+            look_at: DVec3::spline(p0.look_at, p1.look_at, p2.look_at, p3.look_at, t),
+        }
+    }
+}
+
+/// How much light the shutter lets through over the time it is open
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ShutterCurve {
+    /// Fully open from open to close time
+    #[default]
+    Box,
+    /// Opens linearly up to the middle of the interval, then closes
+    /// linearly, giving motion trails soft ends
+    Triangle,
+}
+
+impl ShutterCurve {
+    /// Maps a uniform sample in [0, 1) to a fraction of the shutter interval
+    /// distributed along the curve
+    fn warp(&self, u: f64) -> f64 {
+        match self {
+            ShutterCurve::Box => u,
+            ShutterCurve::Triangle => {
+                if u < 0.5 {
+                    (u / 2.0).sqrt()
+                } else {
+                    1.0 - ((1.0 - u) / 2.0).sqrt()
+                }
+            }
+        }
+    }
+}
+
+/// Viewport placement for one camera pose
+#[derive(Debug, Clone, Copy, Default)]
+struct Frame {
     /// Center of the camera
     center: DVec3,
     /// Location of pixel 0, 0
@@ -33,34 +87,47 @@ pub struct Camera {
     pixel_delta_u: DVec3,
     /// Offset to pixel below
     pixel_delta_v: DVec3,
+    /// Defocus disk horizontal radius
+    defocus_disk_u: DVec3,
+    /// Defocus disk vertical radius
+    defocus_disk_v: DVec3,
+}
+
+#[allow(dead_code)]
+pub struct Camera {
+    /// Ratio of image width over image height
+    aspect_ratio: f64,
+    /// Rendered image width in pixel count
+    image_width: u32,
+    /// Calculated image height
+    image_height: u32,
+    /// Viewport placement for a static camera or the first keyframe
+    frame: Frame,
     /// Count of random sample for each color pixel
     samples_per_pixel: u32,
     /// Maximum number of ray bounces into scene
     max_depth: u32,
     /// Vertical view angle (field of view)
     vfov: f64,
-    /// Point camera is looking from
-    look_from: DVec3,
-    /// Point camera is looking at
-    look_at: DVec3,
+    /// Camera placement over time, a single keyframe for a static camera
+    poses: Keyframes<CameraPose>,
     /// Camera "up" direction
     vup: DVec3,
-    /// X axis basis vector
-    u: DVec3,
-    /// Y axis basis vector
-    v: DVec3,
-    /// Z axis basis vector
-    w: DVec3,
     /// Variation angle of rays through each pixel
     defocus_angle: f64,
     /// Distance from camera lookfrom point to plane of perfect focus
     focus_distance: f64,
-    /// Defocus disk horizontal radius
-    defocus_disk_u: DVec3,
-    /// Defocus disk vertical radius
-    defocus_disk_v: DVec3,
+    /// Time interval during which the shutter is open
+    shutter: Interval,
+    /// Weighting of ray times over the shutter interval
+    shutter_curve: ShutterCurve,
+    /// Radiance of rays escaping the scene
+    background: Background,
+    /// Emitters sampled directly at every diffuse bounce
+    lights: Option<Arc<dyn Hittable>>,
 }
 
+#[derive(Clone, Copy)]
 pub struct Color {
     r: u8,
     g: u8,
@@ -94,28 +161,48 @@ impl Camera {
         T: Hittable,
     {
         let pixels = if config.multi_threaded {
-            self.get_pixel_string_par(world)
+            self.get_pixels_par(world, config.spectral)
         } else {
-            self.get_pixel_string(world)
+            self.get_pixels(world, config.spectral)
         };
 
+        let is_png = Path::new(&config.output_file)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+        if is_png {
+            let bytes = pixels
+                .iter()
+                .flat_map(|c| [c.r, c.g, c.b])
+                .collect::<Vec<u8>>();
+            return image::save_buffer(
+                &config.output_file,
+                &bytes,
+                self.image_width,
+                self.image_height,
+                image::ExtendedColorType::Rgb8,
+            )
+            .map_err(|err| RtwError::ImageError(err.to_string()));
+        }
+
         std::fs::write(
             &config.output_file,
             format!(
                 "P3\n{} {}\n255\n{}\n",
-                self.image_width, self.image_height, pixels,
+                self.image_width,
+                self.image_height,
+                pixels.iter().join("\n"),
             ),
         )
         .map_err(|err| RtwError::IoError(err.to_string()))
         //TODO: Map this properly
     }
 
-    fn get_pixel_string_par<T>(&self, world: &T) -> String
+    fn get_pixels_par<T>(&self, world: &T, spectral: bool) -> Vec<Color>
     where
         T: Hittable,
     {
         let total_pixels = self.image_width as usize * self.image_height as usize;
-        let mut out: Vec<String> = Vec::with_capacity(total_pixels);
+        let mut out: Vec<Color> = Vec::with_capacity(total_pixels);
 
         let available_threads = std::thread::available_parallelism()
             .map(|n| n.get())
@@ -148,10 +235,7 @@ impl Camera {
 
                     for h in start_column..end_column {
                         for w in 0..self.image_width {
-                            chunk_out.push(format!(
-                                "{}",
-                                Color::from(self.render_pixel(w, h as u32, world))
-                            ));
+                            chunk_out.push(Color::from(self.render_pixel(w, h as u32, world, spectral)));
                             thread_pixel_counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
                             thread_bar.set_position(
                                 thread_pixel_counter.load(std::sync::atomic::Ordering::Relaxed),
@@ -171,10 +255,10 @@ impl Camera {
 
         bar.finish();
 
-        out.join("\n")
+        out
     }
 
-    fn get_pixel_string<T>(&self, world: &T) -> String
+    fn get_pixels<T>(&self, world: &T, spectral: bool) -> Vec<Color>
     where
         T: Hittable,
     {
@@ -184,43 +268,116 @@ impl Camera {
             .into_iter()
             .progress_count(self.image_height as u64 * self.image_width as u64)
             .with_style(Self::get_progress_bar_style())
-            .map(|(h, w)| format!("{}", Color::from(self.render_pixel(w, h, world))))
-            .collect::<Vec<String>>()
-            .join("\n")
+            .map(|(h, w)| Color::from(self.render_pixel(w, h, world, spectral)))
+            .collect()
     }
 
-    fn render_pixel<T>(&self, width: u32, height: u32, world: &T) -> DVec3
+    /// Average color of the samples through a pixel. In spectral mode each
+    /// sample traces its own wavelengths, which are turned back into RGB
+    /// before averaging.
+    fn render_pixel<T>(&self, width: u32, height: u32, world: &T, spectral: bool) -> DVec3
     where
         T: Hittable,
     {
         (0..self.samples_per_pixel)
-            .map(|_| self.get_ray(width, height).color(world, self.max_depth))
+            .map(|sample| {
+                let wavelengths = spectral.then(|| SampledWavelengths::sample(rand::random()));
+                let radiance = self
+                    .get_ray(width, height, sample)
+                    .wavelengths(wavelengths)
+                    .color(
+                        world,
+                        self.lights.as_deref(),
+                        &self.background,
+                        self.max_depth,
+                    );
+                match wavelengths {
+                    Some(wavelengths) => wavelengths.to_rgb(radiance),
+                    None => radiance,
+                }
+            })
             .sum::<DVec3>()
             / (self.samples_per_pixel as f64)
     }
 
-    fn get_ray(&self, width: u32, height: u32) -> Ray {
+    /// Places the viewport for the given pose, using the lens settings of
+    /// this camera
+    fn frame_for(&self, pose: CameraPose) -> Frame {
+        let center = pose.look_from;
+
+        // u,v,w unit basis vectors for the camera coordinate frame
+        let w = (pose.look_from - pose.look_at).normalize();
+        let u = DVec3::cross(self.vup, w).normalize();
+        let v = DVec3::cross(w, u);
+
+        let theta = self.vfov.to_radians();
+        let h = (theta / 2.0).tan();
+        let viewport_height = 2.0 * h * self.focus_distance;
+        let viewport_width = viewport_height * (self.image_width as f64 / self.image_height as f64);
+
+        let viewport_u = viewport_width * u;
+        let viewport_v = viewport_height * -v;
+
+        let pixel_delta_u = viewport_u / self.image_width as f64;
+        let pixel_delta_v = viewport_v / self.image_height as f64;
+
+        let viewport_upper_left =
+            center - (viewport_u / 2.0) - (viewport_v / 2.0) - (self.focus_distance * w);
+        let pixel00_location = viewport_upper_left + 0.5 * (pixel_delta_u + pixel_delta_v);
+        let defocus_radius = self.focus_distance * ((self.defocus_angle / 2.0).to_radians()).tan();
+
+        Frame {
+            center,
+            pixel00_location,
+            pixel_delta_u,
+            pixel_delta_v,
+            defocus_disk_u: u * defocus_radius,
+            defocus_disk_v: v * defocus_radius,
+        }
+    }
+
+    /// Time of the given pixel sample. The shutter interval is split into
+    /// one stratum per sample with a random time in each, so the samples of
+    /// a pixel cover the whole interval evenly.
+    fn sample_time(&self, sample: u32) -> f64 {
+        let u = (sample as f64 + rand::rng().random::<f64>()) / self.samples_per_pixel as f64;
+        self.shutter.start + (self.shutter.end - self.shutter.start) * self.shutter_curve.warp(u)
+    }
+
+    fn get_ray(&self, width: u32, height: u32, sample: u32) -> Ray {
+        let time = self.sample_time(sample);
+        let frame = if self.poses.is_static() {
+            self.frame
+        } else {
+            self.frame_for(self.poses.sample(time))
+        };
+
         let offset = sample_square();
-        let pixel_sample = self.pixel00_location
-            + ((width as f64 + offset.x) * self.pixel_delta_u)
-            + ((height as f64 + offset.y) * self.pixel_delta_v);
+        let pixel_sample = frame.pixel00_location
+            + ((width as f64 + offset.x) * frame.pixel_delta_u)
+            + ((height as f64 + offset.y) * frame.pixel_delta_v);
 
         let ray_origin = if self.defocus_angle <= 0.0 {
-            self.center
+            frame.center
         } else {
-            self.defocus_disk_sample()
+            defocus_disk_sample(&frame)
         };
 
-        Ray::with_time(
-            ray_origin,
-            pixel_sample - self.center,
-            Some(rand::rng().random()),
-        )
-    }
+        // Samples within a pixel already average out detail between them,
+        // so the footprint shrinks with more of them, as in pbrt
+        let spacing = (self.samples_per_pixel as f64).sqrt().recip().max(0.125);
+        let differentials = RayDifferentials {
+            x_origin: ray_origin,
+            x_direction: pixel_sample + spacing * frame.pixel_delta_u - ray_origin,
+            y_origin: ray_origin,
+            y_direction: pixel_sample + spacing * frame.pixel_delta_v - ray_origin,
+        };
 
-    fn defocus_disk_sample(&self) -> DVec3 {
-        let p = random_in_unit_disk();
-        self.center + (p.x * self.defocus_disk_u) + (p.y * self.defocus_disk_v)
+        // Aim from the point on the lens at the pixel on the focus plane, so
+        // rays from the whole lens meet there and only objects off that
+        // plane blur
+        Ray::with_time(ray_origin, pixel_sample - ray_origin, Some(time))
+            .differentials(Some(differentials))
     }
 
     fn get_progress_bar_style() -> ProgressStyle {
@@ -231,7 +388,42 @@ impl Camera {
     }
 }
 
+fn defocus_disk_sample(frame: &Frame) -> DVec3 {
+    let p = random_in_unit_disk();
+    frame.center + (p.x * frame.defocus_disk_u) + (p.y * frame.defocus_disk_v)
+}
+
 fn sample_square() -> DVec3 {
     let mut rng = rand::rng();
     DVec3::new(rng.random::<f64>() - 0.5, rng.random::<f64>() - 0.5, 0.0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fraction of evenly spread samples warped below `x`
+    fn warped_below(curve: ShutterCurve, x: f64) -> f64 {
+        let n = 10_000;
+        let below = (0..n)
+            .filter(|i| curve.warp((*i as f64 + 0.5) / n as f64) < x)
+            .count();
+        below as f64 / n as f64
+    }
+
+    #[test]
+    fn shutter_curves_spread_time_along_their_shape() {
+        for i in 0..=10 {
+            let x = i as f64 / 10.0;
+            // Integral of the triangle 4x rising to the middle and falling
+            // back to 0
+            let triangle = if x < 0.5 {
+                2.0 * x * x
+            } else {
+                1.0 - 2.0 * (1.0 - x) * (1.0 - x)
+            };
+            assert!((warped_below(ShutterCurve::Box, x) - x).abs() < 1e-3);
+            assert!((warped_below(ShutterCurve::Triangle, x) - triangle).abs() < 1e-3);
+        }
+    }
+}
@@ -1,7 +1,14 @@
 use glam::DVec3;
+use std::sync::Arc;
 
-use crate::camera::Camera;
+use crate::{
+    background::Background,
+    camera::{Camera, CameraPose, Frame, ShutterCurve},
+    hittable::Hittable,
+    utils::{interval::Interval, keyframe::Keyframes},
+};
 
+#[derive(Clone)]
 pub struct CameraBuilder {
     aspect_ratio: f64,
     image_width: u32,
@@ -13,6 +20,11 @@ pub struct CameraBuilder {
     vup: DVec3,
     defocus_angle: f64,
     defocus_distance: f64,
+    poses: Vec<(f64, CameraPose)>,
+    shutter: Interval,
+    shutter_curve: ShutterCurve,
+    background: Background,
+    lights: Option<Arc<dyn Hittable>>,
 }
 
 impl Default for CameraBuilder {
@@ -28,6 +40,11 @@ impl Default for CameraBuilder {
             vup: DVec3::new(0.0, 1.0, 0.0),
             defocus_angle: 0.0,
             defocus_distance: 10.0,
+            poses: Vec::new(),
+            shutter: 0.0..1.0,
+            shutter_curve: ShutterCurve::default(),
+            background: Background::default(),
+            lights: None,
         }
     }
 }
@@ -40,53 +57,31 @@ impl CameraBuilder {
             ((self.image_width as f64) / self.aspect_ratio) as u32
         };
 
-        let center = self.look_from;
-
-        // u,v,w unit basis vectors for the camera coordinate frame
-        let w = (self.look_from - self.look_at).normalize();
-        let u = DVec3::cross(self.vup, w).normalize();
-        let v = DVec3::cross(w, u);
-
-        let theta = self.vfov.to_radians();
-        let h = (theta / 2.0).tan();
-        let viewport_height = 2.0 * h * self.defocus_distance;
-        let viewport_width = viewport_height * (self.image_width as f64 / image_height as f64);
-
-        let viewport_u = viewport_width * u;
-        let viewport_v = viewport_height * -v;
-
-        let pixel_delta_u = viewport_u / self.image_width as f64;
-        let pixel_delta_v = viewport_v / image_height as f64;
-
-        let viewport_upper_left =
-            center - (viewport_u / 2.0) - (viewport_v / 2.0) - (self.defocus_distance * w);
-        let pixel00_location = viewport_upper_left + 0.5 * (pixel_delta_u + pixel_delta_v);
-        let defocus_radius = self.defocus_distance * ((self.defocus_angle/ 2.0).to_radians()).tan();
-        let defocus_disk_u = u * defocus_radius;
-        let defocus_disk_v = v * defocus_radius;
+        // Without keyframes the camera stays at `look_from` and `look_at`
+        let poses = Keyframes::new(self.poses.clone()).unwrap_or(Keyframes::constant(CameraPose {
+            look_from: self.look_from,
+            look_at: self.look_at,
+        }));
 
-        Camera {
+        let mut camera = Camera {
             aspect_ratio: self.aspect_ratio,
             image_width: self.image_width,
             image_height,
-            center,
-            pixel00_location,
-            pixel_delta_u,
-            pixel_delta_v,
+            frame: Frame::default(),
             samples_per_pixel: self.samples_per_pixel,
             max_depth: self.max_depth,
             vfov: self.vfov,
-            look_from: self.look_from,
-            look_at: self.look_at,
+            poses,
             vup: self.vup,
-            u,
-            v,
-            w,
             defocus_angle: self.defocus_angle,
             focus_distance: self.defocus_distance,
-            defocus_disk_u,
-            defocus_disk_v,
-        }
+            shutter: self.shutter.clone(),
+            shutter_curve: self.shutter_curve,
+            background: self.background.clone(),
+            lights: self.lights.clone(),
+        };
+        camera.frame = camera.frame_for(camera.poses.first());
+        camera
     }
 
     pub fn image_width(mut self, image_width: u32) -> Self {
@@ -138,4 +133,40 @@ impl CameraBuilder {
         self.defocus_distance = distance;
         self
     }
+
+    /// Adds a camera placement at `time`. Rays are generated from the pose
+    /// interpolated at their time, which blurs a camera moving while the
+    /// shutter is open. Overrides `look_from` and `look_at` when set.
+    pub fn pose_keyframe(mut self, time: f64, look_from: DVec3, look_at: DVec3) -> Self {
+        self.poses.push((time, CameraPose { look_from, look_at }));
+        self
+    }
+
+    pub fn has_pose_keyframes(&self) -> bool {
+        !self.poses.is_empty()
+    }
+
+    /// Times at which the shutter opens and closes. Ray times are spread
+    /// in between following the shutter curve.
+    pub fn shutter(mut self, open: f64, close: f64) -> Self {
+        self.shutter = open..close;
+        self
+    }
+
+    pub fn shutter_curve(mut self, curve: ShutterCurve) -> Self {
+        self.shutter_curve = curve;
+        self
+    }
+
+    pub fn background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Objects to aim extra rays at, typically the emissive ones. They must
+    /// also be part of the rendered world.
+    pub fn lights(mut self, lights: Arc<dyn Hittable>) -> Self {
+        self.lights = Some(lights);
+        self
+    }
 }
@@ -0,0 +1,170 @@
+use crate::{
+    Result, RtwError,
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    ray::Ray,
+    utils::{
+        interval::Interval,
+        keyframe::{Interpolate, Keyframes},
+    },
+};
+use glam::{DQuat, DVec3};
+use std::sync::Arc;
+
+/// Number of extra samples between two keyframes used to bound rotations
+const BBOX_STEPS: usize = 16;
+
+/// Rigid transform with uniform scale, applied as scale, rotate, translate
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    pub translation: DVec3,
+    pub rotation: DQuat,
+    pub scale: f64,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: DVec3::ZERO,
+            rotation: DQuat::IDENTITY,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Transform {
+    pub fn translation(translation: DVec3) -> Self {
+        Self {
+            translation,
+            ..Default::default()
+        }
+    }
+
+    pub fn rotation(mut self, rotation: DQuat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Uniform scale, which must be positive. [`Animated::new`] rejects
+    /// transforms that shrink an object to nothing or mirror it.
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    fn apply(&self, p: DVec3) -> DVec3 {
+        self.rotation * (self.scale * p) + self.translation
+    }
+}
+
+impl Interpolate for Transform {
+    fn interpolate(a: Self, b: Self, t: f64) -> Self {
+        Self {
+            translation: DVec3::interpolate(a.translation, b.translation, t),
+            rotation: DQuat::interpolate(a.rotation, b.rotation, t),
+            scale: f64::interpolate(a.scale, b.scale, t),
+        }
+    }
+
+    fn spline(p0: Self, p1: Self, p2: Self, p3: Self, t: f64) -> Self {
+        Self {
+            translation: DVec3::spline(
+                p0.translation,
+                p1.translation,
+                p2.translation,
+                p3.translation,
+                t,
+            ),
+            rotation: DQuat::interpolate(p1.rotation, p2.rotation, t),
+            scale: f64::spline(p0.scale, p1.scale, p2.scale, p3.scale, t),
+        }
+    }
+}
+
+/// Moves any object along keyframed transforms, sampled at the ray time.
+///
+/// Rays without a time see the object at its first keyframe.
+pub struct Animated {
+    object: Arc<dyn Hittable>,
+    keys: Keyframes<Transform>,
+    bbox: Aabb,
+}
+
+impl Animated {
+    pub fn new(object: Arc<dyn Hittable>, keys: Keyframes<Transform>) -> Result<Self> {
+        let local = object.bounding_box();
+        let times = keys.times().collect::<Vec<f64>>();
+
+        let mut samples = vec![times[0]];
+        for (t0, t1) in times.iter().zip(times.iter().skip(1)) {
+            samples.extend((1..=BBOX_STEPS).map(|i| t0 + (t1 - t0) * i as f64 / BBOX_STEPS as f64));
+        }
+
+        let mut bbox = transformed_bbox(&local, &keys.sample(samples[0]))?;
+        for time in samples.into_iter().skip(1) {
+            bbox = Aabb::from_aabbs(&bbox, &transformed_bbox(&local, &keys.sample(time))?);
+        }
+
+        Ok(Self { object, keys, bbox })
+    }
+}
+
+fn transformed_bbox(bbox: &Aabb, transform: &Transform) -> Result<Aabb> {
+    // Rays are moved into object space by dividing by the scale
+    if transform.scale <= 0.0 || !transform.scale.is_finite() {
+        return Err(RtwError::InvalidScale(transform.scale));
+    }
+    let corners = bbox.corners().map(|c| transform.apply(c));
+    let min = corners.iter().fold(DVec3::INFINITY, |acc, c| acc.min(*c));
+    let max = corners
+        .iter()
+        .fold(DVec3::NEG_INFINITY, |acc, c| acc.max(*c));
+    Aabb::from_points(min, max)
+}
+
+impl Hittable for Animated {
+    fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let transform = match ray.time {
+            Some(time) => self.keys.sample(time),
+            None => self.keys.first(),
+        };
+
+        // Move the ray into object space, keeping `t` unchanged
+        let inverse = transform.rotation.inverse();
+        let local_ray = Ray::with_time(
+            inverse * (ray.origin - transform.translation) / transform.scale,
+            inverse * ray.direction / transform.scale,
+            ray.time,
+        );
+
+        let mut hr = self.object.hit(&local_ray, interval)?;
+        hr.point = transform.apply(hr.point);
+        hr.normal = transform.rotation * hr.normal;
+        hr.dpdu = transform.rotation * (transform.scale * hr.dpdu);
+        hr.dpdv = transform.rotation * (transform.scale * hr.dpdv);
+        Some(hr)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox.clone()
+    }
+
+    /// Light sampling sees the object at its first keyframe. Solid angles
+    /// are unchanged by rotation and uniform scale, so the object density
+    /// applies as is.
+    fn pdf_value(&self, origin: DVec3, direction: DVec3) -> f64 {
+        let transform = self.keys.first();
+        let inverse = transform.rotation.inverse();
+        self.object.pdf_value(
+            inverse * (origin - transform.translation) / transform.scale,
+            inverse * direction,
+        )
+    }
+
+    fn random(&self, origin: DVec3) -> DVec3 {
+        let transform = self.keys.first();
+        let inverse = transform.rotation.inverse();
+        let local_origin = inverse * (origin - transform.translation) / transform.scale;
+        transform.rotation * self.object.random(local_origin)
+    }
+}
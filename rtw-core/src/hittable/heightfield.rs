@@ -0,0 +1,398 @@
+use crate::{
+    Result, RtwError,
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    utils::interval::{Interval, Pad},
+};
+use glam::DVec3;
+use std::sync::Arc;
+
+/// Terrain defined by a regular grid of height samples over the xz plane.
+///
+/// Each grid cell is split into two triangles. Rays walk the grid cell by
+/// cell (2D DDA) and only test the triangles of cells whose height range
+/// overlaps the ray, so the cost grows with the grid resolution along the
+/// ray rather than with the total number of samples.
+pub struct Heightfield {
+    /// Corner of the grid with the smallest x and z, `y` is the base height
+    origin: DVec3,
+    /// Extent of the grid along x and z, `y` scales the normalized heights
+    size: DVec3,
+    /// Number of samples along x
+    nx: usize,
+    /// Number of samples along z
+    nz: usize,
+    /// Normalized heights, row major with x varying fastest
+    heights: Vec<f64>,
+    /// Smooth per-sample normals used for shading
+    normals: Vec<DVec3>,
+    /// Minimum and maximum world height of each cell
+    cell_bounds: Vec<(f64, f64)>,
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+impl Heightfield {
+    /// Samples `f(u, v)` on a `nx` by `nz` grid, with `u` and `v` in [0, 1].
+    /// The returned heights are scaled by `size.y`.
+    pub fn from_fn<F>(
+        origin: DVec3,
+        size: DVec3,
+        nx: usize,
+        nz: usize,
+        f: F,
+        mat: Arc<dyn Material>,
+    ) -> Result<Self>
+    where
+        F: Fn(f64, f64) -> f64,
+    {
+        if nx < 2 || nz < 2 {
+            return Err(RtwError::InvalidResolution(nx, nz));
+        }
+
+        let heights = (0..nz)
+            .flat_map(|j| (0..nx).map(move |i| (i, j)))
+            .map(|(i, j)| f(i as f64 / (nx - 1) as f64, j as f64 / (nz - 1) as f64))
+            .collect();
+
+        Self::from_heights(origin, size, nx, nz, heights, mat)
+    }
+
+    /// Loads a grayscale height map, black being `origin.y` and white being
+    /// `origin.y + size.y`. Color images are converted to luminance.
+    pub fn from_image(
+        path: &str,
+        origin: DVec3,
+        size: DVec3,
+        mat: Arc<dyn Material>,
+    ) -> Result<Self> {
+        let img = image::open(path)
+            .map_err(|err| RtwError::ImageError(err.to_string()))?
+            .into_luma16();
+        let (nx, nz) = (img.width() as usize, img.height() as usize);
+        if nx < 2 || nz < 2 {
+            return Err(RtwError::InvalidResolution(nx, nz));
+        }
+
+        let heights = img
+            .pixels()
+            .map(|p| p.0[0] as f64 / u16::MAX as f64)
+            .collect();
+
+        Self::from_heights(origin, size, nx, nz, heights, mat)
+    }
+
+    fn from_heights(
+        origin: DVec3,
+        size: DVec3,
+        nx: usize,
+        nz: usize,
+        heights: Vec<f64>,
+        mat: Arc<dyn Material>,
+    ) -> Result<Self> {
+        let mut field = Self {
+            origin,
+            size,
+            nx,
+            nz,
+            heights,
+            normals: Vec::new(),
+            cell_bounds: Vec::new(),
+            mat,
+            bbox: Aabb::default(),
+        };
+
+        field.normals = (0..nz)
+            .flat_map(|j| (0..nx).map(move |i| (i, j)))
+            .map(|(i, j)| field.sample_normal(i, j))
+            .collect();
+
+        field.cell_bounds = (0..nz - 1)
+            .flat_map(|j| (0..nx - 1).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let corners = [
+                    field.vertex(i, j).y,
+                    field.vertex(i + 1, j).y,
+                    field.vertex(i, j + 1).y,
+                    field.vertex(i + 1, j + 1).y,
+                ];
+                corners
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &y| {
+                        (lo.min(y), hi.max(y))
+                    })
+            })
+            .collect();
+
+        let (y_min, y_max) = field
+            .cell_bounds
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &(a, b)| {
+                (lo.min(a), hi.max(b))
+            });
+        // A perfectly flat terrain would produce an empty y interval
+        let y = (y_min..y_max).pad(1e-4);
+        field.bbox = Aabb::from_points(
+            DVec3::new(origin.x, y.start, origin.z),
+            DVec3::new(origin.x + size.x, y.end, origin.z + size.z),
+        )?;
+
+        Ok(field)
+    }
+
+    fn cell_size(&self) -> (f64, f64) {
+        (
+            self.size.x / (self.nx - 1) as f64,
+            self.size.z / (self.nz - 1) as f64,
+        )
+    }
+
+    fn height(&self, i: usize, j: usize) -> f64 {
+        self.heights[j * self.nx + i]
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> DVec3 {
+        let (dx, dz) = self.cell_size();
+        self.origin
+            + DVec3::new(
+                i as f64 * dx,
+                self.height(i, j) * self.size.y,
+                j as f64 * dz,
+            )
+    }
+
+    /// Normal at a grid sample from central differences of the neighbours
+    fn sample_normal(&self, i: usize, j: usize) -> DVec3 {
+        let (i0, i1) = (i.saturating_sub(1), (i + 1).min(self.nx - 1));
+        let (j0, j1) = (j.saturating_sub(1), (j + 1).min(self.nz - 1));
+
+        let tangent_x = self.vertex(i1, j) - self.vertex(i0, j);
+        let tangent_z = self.vertex(i, j1) - self.vertex(i, j0);
+        tangent_z.cross(tangent_x).normalize()
+    }
+
+    /// Intersects the two triangles of cell (i, j), returning the closest hit
+    /// as (t, point, triangle normal, smooth normal). Both normals point up.
+    fn hit_cell(
+        &self,
+        ray: &Ray,
+        i: usize,
+        j: usize,
+        interval: &Interval,
+    ) -> Option<(f64, DVec3, DVec3, DVec3)> {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let triangles = [[0, 1, 2], [0, 2, 3]];
+
+        triangles
+            .iter()
+            .filter_map(|tri| {
+                let [a, b, c] = tri.map(|k| corners[k]);
+                let [v0, v1, v2] = [a, b, c].map(|(i, j)| self.vertex(i, j));
+                let (t, b1, b2) = hit_triangle(ray, v0, v1, v2)?;
+                if !interval.contains(&t) {
+                    return None;
+                }
+                let geometric = (v2 - v0).cross(v1 - v0).normalize();
+                let normal = (1.0 - b1 - b2) * self.normals[a.1 * self.nx + a.0]
+                    + b1 * self.normals[b.1 * self.nx + b.0]
+                    + b2 * self.normals[c.1 * self.nx + c.0];
+                Some((t, ray.at(t), geometric, normal.normalize()))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+}
+
+/// Möller–Trumbore ray/triangle intersection returning (t, b1, b2), where
+/// b1 and b2 are the barycentric weights of `v1` and `v2`.
+fn hit_triangle(ray: &Ray, v0: DVec3, v1: DVec3, v2: DVec3) -> Option<(f64, f64, f64)> {
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let pvec = ray.direction.cross(edge2);
+    let det = edge1.dot(pvec);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+
+    let inv_det = det.recip();
+    let tvec = ray.origin - v0;
+    let b1 = tvec.dot(pvec) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+
+    let qvec = tvec.cross(edge1);
+    let b2 = ray.direction.dot(qvec) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+
+    Some((edge2.dot(qvec) * inv_det, b1, b2))
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let bounds = self.bbox.hit(ray, interval.clone())?;
+        let (dx, dz) = self.cell_size();
+
+        let entry = ray.at(bounds.start);
+        let cell = |coord: f64, origin: f64, delta: f64, count: usize| {
+            (((coord - origin) / delta).floor().max(0.0) as usize).min(count - 2)
+        };
+        let mut i = cell(entry.x, self.origin.x, dx, self.nx);
+        let mut j = cell(entry.z, self.origin.z, dz, self.nz);
+
+        // Parametric distance to the next cell boundary along x and z
+        let next_boundary = |index: usize, dir: f64, origin: f64, delta: f64, orig: f64| {
+            if dir > 0.0 {
+                (origin + (index + 1) as f64 * delta - orig) / dir
+            } else if dir < 0.0 {
+                (origin + index as f64 * delta - orig) / dir
+            } else {
+                f64::INFINITY
+            }
+        };
+        let mut t_max_x = next_boundary(i, ray.direction.x, self.origin.x, dx, ray.origin.x);
+        let mut t_max_z = next_boundary(j, ray.direction.z, self.origin.z, dz, ray.origin.z);
+        let t_delta_x = (dx / ray.direction.x).abs();
+        let t_delta_z = (dz / ray.direction.z).abs();
+
+        let mut t_enter = bounds.start;
+        loop {
+            let t_exit = t_max_x.min(t_max_z).min(bounds.end);
+
+            let (y_min, y_max) = self.cell_bounds[j * (self.nx - 1) + i];
+            let y_enter = ray.at(t_enter).y;
+            let y_exit = ray.at(t_exit).y;
+            if y_enter.min(y_exit) <= y_max
+                && y_enter.max(y_exit) >= y_min
+                && let Some((t, point, geometric, normal)) = self.hit_cell(ray, i, j, &interval)
+            {
+                let uv = (
+                    (point.x - self.origin.x) / self.size.x,
+                    (point.z - self.origin.z) / self.size.z,
+                );
+                let hr = HitRecord::init(point, geometric, t, uv, ray, self.mat.clone())
+                    .shading_normal(normal);
+                // Slope of the surface along x and z, from the normal
+                return Some(if normal.y > 1e-6 {
+                    hr.tangents(
+                        DVec3::new(self.size.x, -self.size.x * normal.x / normal.y, 0.0),
+                        DVec3::new(0.0, -self.size.z * normal.z / normal.y, self.size.z),
+                    )
+                } else {
+                    hr
+                });
+            }
+
+            if t_exit >= bounds.end {
+                return None;
+            }
+
+            if t_max_x < t_max_z {
+                if ray.direction.x > 0.0 {
+                    i += 1;
+                    if i >= self.nx - 1 {
+                        return None;
+                    }
+                } else {
+                    i = i.checked_sub(1)?;
+                }
+                t_max_x += t_delta_x;
+            } else {
+                if ray.direction.z > 0.0 {
+                    j += 1;
+                    if j >= self.nz - 1 {
+                        return None;
+                    }
+                } else {
+                    j = j.checked_sub(1)?;
+                }
+                t_max_z += t_delta_z;
+            }
+            t_enter = t_exit;
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::lambertian::Lambertian;
+    use rand::Rng;
+
+    /// Closest hit over every cell of the grid, without walking it
+    fn brute_force(field: &Heightfield, ray: &Ray, interval: &Interval) -> Option<f64> {
+        (0..field.nz - 1)
+            .flat_map(|j| (0..field.nx - 1).map(move |i| (i, j)))
+            .filter_map(|(i, j)| field.hit_cell(ray, i, j, interval))
+            .map(|(t, ..)| t)
+            .min_by(f64::total_cmp)
+    }
+
+    #[test]
+    fn grid_walk_matches_brute_force() {
+        let field = Heightfield::from_fn(
+            DVec3::new(-2.0, 0.0, -3.0),
+            DVec3::new(4.0, 1.5, 6.0),
+            17,
+            23,
+            |u, v| 0.5 + 0.5 * (7.0 * u).sin() * (5.0 * v).cos(),
+            Arc::new(Lambertian::from_color(DVec3::ONE)),
+        )
+        .unwrap();
+
+        let mut rng = rand::rng();
+        let mut hits = 0;
+        for _ in 0..2000 {
+            let origin = DVec3::new(
+                rng.random_range(-4.0..4.0),
+                rng.random_range(-1.0..4.0),
+                rng.random_range(-5.0..5.0),
+            );
+            let target = DVec3::new(
+                rng.random_range(-2.0..2.0),
+                rng.random_range(0.0..1.5),
+                rng.random_range(-3.0..3.0),
+            );
+            let ray = Ray::new(origin, target - origin);
+            let interval = 0.001..f64::INFINITY;
+
+            let walked = field.hit(&ray, interval.clone()).map(|hr| hr.t);
+            let expected = brute_force(&field, &ray, &interval);
+            match (walked, expected) {
+                (Some(a), Some(b)) => assert!((a - b).abs() < 1e-9, "{a} vs {b}"),
+                (None, None) => {}
+                _ => panic!("walk {walked:?} vs brute force {expected:?} for {origin} {target}"),
+            }
+            hits += walked.is_some() as u32;
+        }
+        assert!(hits > 100);
+    }
+
+    #[test]
+    fn side_of_hit_follows_the_triangle() {
+        // Steep ridge whose smooth normal near the top points straight up
+        let field = Heightfield::from_fn(
+            DVec3::ZERO,
+            DVec3::new(2.0, 4.0, 2.0),
+            3,
+            3,
+            |u, _| if u == 0.5 { 1.0 } else { 0.0 },
+            Arc::new(Lambertian::from_color(DVec3::ONE)),
+        )
+        .unwrap();
+
+        // Rising ray that hits the slope from outside but runs along the
+        // smooth normal
+        let ray = Ray::new(DVec3::new(-1.0, 3.0, 0.5), DVec3::new(1.0, 0.4, 0.0));
+        let hr = field.hit(&ray, 0.001..f64::INFINITY).unwrap();
+        assert!(hr.front_face);
+        assert!(hr.normal.y > 0.0 && hr.normal.dot(ray.direction) > 0.0);
+    }
+}
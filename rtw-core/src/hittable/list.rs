@@ -3,6 +3,8 @@ use crate::{
     hittable::{HitRecord, Hittable},
     ray::Ray, utils::interval::Interval,
 };
+use glam::DVec3;
+use rand::seq::IndexedRandom;
 use std::sync::Arc;
 
 #[derive(Default)]
@@ -42,4 +44,25 @@ impl Hittable for HittableList {
     fn bounding_box(&self) -> Aabb {
         self.bbox.clone()
     }
+
+    /// Picks one of the objects uniformly, so the density is the average of
+    /// the object densities
+    fn pdf_value(&self, origin: DVec3, direction: DVec3) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+        let sum = self
+            .objects
+            .iter()
+            .map(|obj| obj.pdf_value(origin, direction))
+            .sum::<f64>();
+        sum / self.objects.len() as f64
+    }
+
+    fn random(&self, origin: DVec3) -> DVec3 {
+        match self.objects.choose(&mut rand::rng()) {
+            Some(obj) => obj.random(origin),
+            None => DVec3::X,
+        }
+    }
 }
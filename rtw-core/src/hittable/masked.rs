@@ -0,0 +1,87 @@
+use glam::DVec3;
+use rand::Rng;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    ray::Ray,
+    texture::Texture,
+    utils::interval::Interval,
+};
+
+/// How the opacity of a hit decides whether it counts
+#[derive(Debug, Clone, Copy)]
+pub enum Cutout {
+    /// Hits with an opacity below the threshold are cut out
+    Threshold(f64),
+    /// Hits are kept with a chance equal to their opacity, which gives soft
+    /// edges and partly see-through surfaces once averaged over samples
+    Stochastic,
+}
+
+/// Cuts holes into any object with an opacity texture, such as leaves or a
+/// chain-link fence drawn on a quad.
+///
+/// Holes are skipped while looking for the hit, so rays go on to whatever
+/// is behind them in the same [`HittableList`](super::list::HittableList)
+/// or [`BvhNode`](super::bvh_node::BvhNode) traversal, shadow rays
+/// included.
+pub struct Masked {
+    object: Arc<dyn Hittable>,
+    /// Opacity read from the first channel
+    opacity: Arc<dyn Texture>,
+    cutout: Cutout,
+}
+
+impl Masked {
+    /// Cuts out hits less than half opaque
+    pub fn new(object: Arc<dyn Hittable>, opacity: Arc<dyn Texture>) -> Self {
+        Self {
+            object,
+            opacity,
+            cutout: Cutout::Threshold(0.5),
+        }
+    }
+
+    pub fn cutout(mut self, cutout: Cutout) -> Self {
+        self.cutout = cutout;
+        self
+    }
+
+    fn is_opaque(&self, hr: &HitRecord) -> bool {
+        let opacity = self.opacity.value(hr.u, hr.v, hr.point).x;
+        match self.cutout {
+            Cutout::Threshold(threshold) => opacity >= threshold,
+            Cutout::Stochastic => rand::rng().random::<f64>() < opacity,
+        }
+    }
+}
+
+impl Hittable for Masked {
+    fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let mut start = interval.start;
+        loop {
+            let hr = self.object.hit(ray, start..interval.end)?;
+            if self.is_opaque(&hr) {
+                return Some(hr);
+            }
+            // Look again just past the cut out hit
+            start = hr.t.next_up();
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.object.bounding_box()
+    }
+
+    /// Lights with holes are sampled as if they had none, directions through
+    /// a hole simply find no light
+    fn pdf_value(&self, origin: DVec3, direction: DVec3) -> f64 {
+        self.object.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: DVec3) -> DVec3 {
+        self.object.random(origin)
+    }
+}
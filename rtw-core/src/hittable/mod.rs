@@ -1,19 +1,33 @@
+pub(crate) mod animated;
 pub(crate) mod bvh_node;
+pub(crate) mod heightfield;
 pub(crate) mod list;
+pub(crate) mod masked;
+pub(crate) mod quad;
 pub(crate) mod sphere;
 
-use crate::{aabb::Aabb, material::Material, ray::Ray, utils::interval::Interval};
+use crate::{
+    aabb::Aabb, material::Material, ray::Ray, texture::Footprint, utils::interval::Interval,
+};
 use glam::DVec3;
 use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct HitRecord {
     pub point: DVec3,
     pub normal: DVec3,
+    /// Change of `point` along `u` and `v`. Normal and bump maps tilt the
+    /// normal in the frame they make.
+    pub dpdu: DVec3,
+    pub dpdv: DVec3,
     pub t: f64,
     pub u: f64,
     pub v: f64,
     pub front_face: bool,
     pub material: Arc<dyn Material>,
+    /// Area around the hit covered by the camera sample, for rays that
+    /// track their differentials
+    pub footprint: Option<Footprint>,
 }
 
 impl HitRecord {
@@ -21,6 +35,7 @@ impl HitRecord {
         point: DVec3,
         outward_normal: DVec3,
         t: f64,
+        (u, v): (f64, f64),
         ray: &Ray,
         mat: Arc<dyn Material>,
     ) -> Self {
@@ -31,20 +46,62 @@ impl HitRecord {
             -outward_normal
         };
 
+        // Any frame around the normal, for surfaces without a meaningful one
+        let helper = if outward_normal.x.abs() > 0.9 {
+            DVec3::Y
+        } else {
+            DVec3::X
+        };
+        let dpdu = helper.cross(outward_normal).normalize();
+        let dpdv = outward_normal.cross(dpdu);
+
         HitRecord {
             point,
             normal,
+            dpdu,
+            dpdv,
             t,
             front_face,
             material: mat,
-            u: 0.0,
-            v: 0.0,
+            u,
+            v,
+            footprint: None,
         }
     }
+
+    /// Sets the derivatives of the surface point along `u` and `v`
+    pub fn tangents(mut self, dpdu: DVec3, dpdv: DVec3) -> Self {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+        self
+    }
+
+    /// Shades with an interpolated `outward_normal` instead of the true
+    /// surface normal. The side of the hit still follows the true surface.
+    pub fn shading_normal(mut self, outward_normal: DVec3) -> Self {
+        self.normal = if self.front_face {
+            outward_normal
+        } else {
+            -outward_normal
+        };
+        self
+    }
 }
 
 pub trait Hittable: Send + Sync {
     fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord>;
 
     fn bounding_box(&self) -> Aabb;
+
+    /// Solid angle density of sampling `direction` from `origin` with
+    /// [`Hittable::random`]. Objects that can't be sampled as lights have
+    /// zero density everywhere.
+    fn pdf_value(&self, _origin: DVec3, _direction: DVec3) -> f64 {
+        0.0
+    }
+
+    /// Random direction from `origin` towards a point on this object
+    fn random(&self, _origin: DVec3) -> DVec3 {
+        DVec3::X
+    }
 }
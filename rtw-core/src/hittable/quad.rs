@@ -0,0 +1,160 @@
+use crate::{
+    Result, RtwError,
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable, list::HittableList},
+    material::Material,
+    ray::Ray,
+    utils::interval::Interval,
+};
+use glam::DVec3;
+use rand::Rng;
+use std::sync::Arc;
+
+/// Parallelogram spanned by `u` and `v` from the corner `q`
+pub struct Quad {
+    q: DVec3,
+    u: DVec3,
+    v: DVec3,
+    /// Cached `n / (n . n)` used to find the planar coordinates of a hit
+    w: DVec3,
+    normal: DVec3,
+    /// Plane offset, `normal . p = d` for every point on the plane
+    d: f64,
+    area: f64,
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+impl Quad {
+    pub fn new(q: DVec3, u: DVec3, v: DVec3, mat: Arc<dyn Material>) -> Result<Self> {
+        let n = u.cross(v);
+        if n.length_squared() == 0.0 {
+            return Err(RtwError::DegenerateQuad);
+        }
+        let normal = n.normalize();
+
+        // A flat quad has no extent along its normal, so pad the box
+        let min = q.min(q + u + v).min(q + u).min(q + v);
+        let max = q.max(q + u + v).max(q + u).max(q + v);
+        let bbox = Aabb::new(min.x..max.x, min.y..max.y, min.z..max.z).pad(1e-4);
+
+        Ok(Self {
+            q,
+            u,
+            v,
+            w: n / n.dot(n),
+            normal,
+            d: normal.dot(q),
+            area: n.length(),
+            mat,
+            bbox,
+        })
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let denom = self.normal.dot(ray.direction);
+        // Ray parallel to the plane
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(ray.origin)) / denom;
+        if !interval.contains(&t) {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let planar = point - self.q;
+        let alpha = self.w.dot(planar.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        Some(
+            HitRecord::init(
+                point,
+                self.normal,
+                t,
+                (alpha, beta),
+                ray,
+                self.mat.clone(),
+            )
+            .tangents(self.u, self.v),
+        )
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox.clone()
+    }
+
+    fn pdf_value(&self, origin: DVec3, direction: DVec3) -> f64 {
+        let Some(hr) = self.hit(&Ray::new(origin, direction), 0.001..f64::INFINITY) else {
+            return 0.0;
+        };
+
+        let distance_squared = hr.t * hr.t * direction.length_squared();
+        let cosine = (direction.dot(hr.normal) / direction.length()).abs();
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: DVec3) -> DVec3 {
+        let mut rng = rand::rng();
+        let p = self.q + rng.random::<f64>() * self.u + rng.random::<f64>() * self.v;
+        p - origin
+    }
+}
+
+/// Axis aligned box with opposite corners `a` and `b`, made of six quads
+pub fn quad_box(a: DVec3, b: DVec3, mat: Arc<dyn Material>) -> Result<HittableList> {
+    let mut sides = HittableList::default();
+
+    let min = a.min(b);
+    let max = a.max(b);
+
+    let dx = DVec3::new(max.x - min.x, 0.0, 0.0);
+    let dy = DVec3::new(0.0, max.y - min.y, 0.0);
+    let dz = DVec3::new(0.0, 0.0, max.z - min.z);
+
+    // front, right, back, left, top, bottom
+    sides.add(Arc::new(Quad::new(
+        DVec3::new(min.x, min.y, max.z),
+        dx,
+        dy,
+        mat.clone(),
+    )?));
+    sides.add(Arc::new(Quad::new(
+        DVec3::new(max.x, min.y, max.z),
+        -dz,
+        dy,
+        mat.clone(),
+    )?));
+    sides.add(Arc::new(Quad::new(
+        DVec3::new(max.x, min.y, min.z),
+        -dx,
+        dy,
+        mat.clone(),
+    )?));
+    sides.add(Arc::new(Quad::new(
+        DVec3::new(min.x, min.y, min.z),
+        dz,
+        dy,
+        mat.clone(),
+    )?));
+    sides.add(Arc::new(Quad::new(
+        DVec3::new(min.x, max.y, max.z),
+        dx,
+        -dz,
+        mat.clone(),
+    )?));
+    sides.add(Arc::new(Quad::new(
+        DVec3::new(min.x, min.y, min.z),
+        dx,
+        dz,
+        mat,
+    )?));
+
+    Ok(sides)
+}
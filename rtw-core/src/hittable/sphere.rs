@@ -3,16 +3,20 @@ use crate::{
     hittable::{HitRecord, Hittable},
     material::Material,
     ray::Ray,
-    utils::interval::Interval,
+    utils::{interval::Interval, onb::Onb},
     Result, RtwError,
 };
 use glam::DVec3;
-use std::sync::Arc;
+use rand::Rng;
+use std::{f64::consts::PI, sync::Arc};
 
 pub struct Sphere {
+    /// Center at time 0
     pub center: DVec3,
     pub radius: f64,
     pub mat: Arc<dyn Material>,
+    /// Center at time 1 for a moving sphere. The center moves linearly
+    /// in between and stays put before time 0 and after time 1.
     pub new_center: Option<DVec3>,
     pub bbox: Aabb,
 }
@@ -44,7 +48,7 @@ impl Sphere {
         }
         let rvec = DVec3::splat(radius);
         let bbox1 = Aabb::from_points(center - rvec, center + rvec)?;
-        let bbox2 = Aabb::from_points(center + new_center - rvec, center + new_center + rvec)?;
+        let bbox2 = Aabb::from_points(new_center - rvec, new_center + rvec)?;
         Ok(Self {
             center,
             radius,
@@ -55,22 +59,36 @@ impl Sphere {
     }
 
     fn current_center(&self, ray: &Ray) -> DVec3 {
-        if let Some(center2) = self.new_center {
-            if let Some(current_time) = ray.time {
-                self.center + current_time * center2
-            } else {
-                self.center
-            }
-        } else {
-            self.center
+        match (self.new_center, ray.time) {
+            (Some(center2), Some(time)) => self.center.lerp(center2, time.clamp(0.0, 1.0)),
+            _ => self.center,
         }
     }
 }
 
+/// Maps a point on the unit sphere to (u, v), with `u` the angle around the
+/// y axis starting from -x and `v` the angle from the -y pole, both in [0, 1].
+fn sphere_uv(p: DVec3) -> (f64, f64) {
+    let theta = (-p.y).acos();
+    let phi = f64::atan2(-p.z, p.x) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
+/// Derivatives of the point at `p` on the sphere along the [`sphere_uv`]
+/// coordinates, None at the poles where `u` is undefined
+fn sphere_tangents(p: DVec3, radius: f64) -> Option<(DVec3, DVec3)> {
+    let sin_theta = (1.0 - p.y * p.y).max(0.0).sqrt();
+    if sin_theta < 1e-6 {
+        return None;
+    }
+    let dpdu = 2.0 * PI * radius * DVec3::new(p.z, 0.0, -p.x);
+    let dpdv = PI * radius * DVec3::new(-p.x * p.y, sin_theta * sin_theta, -p.z * p.y) / sin_theta;
+    Some((dpdu, dpdv))
+}
+
 impl Hittable for Sphere {
     fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
         let current_center = self.current_center(ray);
-        let current_center = self.center;
         let oc = current_center - ray.origin;
         let a = ray.direction.length_squared();
         let h = ray.direction.dot(oc);
@@ -93,10 +111,54 @@ impl Hittable for Sphere {
 
         let p = ray.at(root);
         let normal = (p - current_center) / self.radius;
-        Some(HitRecord::init(p, normal, root, ray, self.mat.clone()))
+        let uv = sphere_uv(normal);
+        let hr = HitRecord::init(p, normal, root, uv, ray, self.mat.clone());
+        Some(match sphere_tangents(normal, self.radius) {
+            Some((dpdu, dpdv)) => hr.tangents(dpdu, dpdv),
+            None => hr,
+        })
     }
 
     fn bounding_box(&self) -> Aabb {
         self.bbox.clone()
     }
+
+    /// Density of the cone of directions subtended by the sphere. Moving
+    /// spheres are sampled at their time 0 position.
+    fn pdf_value(&self, origin: DVec3, direction: DVec3) -> f64 {
+        if self
+            .hit(&Ray::new(origin, direction), 0.001..f64::INFINITY)
+            .is_none()
+        {
+            return 0.0;
+        }
+
+        let distance_squared = (self.center - origin).length_squared();
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared)
+            .max(0.0)
+            .sqrt();
+        let solid_angle = 2.0 * PI * (1.0 - cos_theta_max);
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: DVec3) -> DVec3 {
+        let direction = self.center - origin;
+        let uvw = Onb::new(direction);
+        uvw.transform(random_to_sphere(self.radius, direction.length_squared()))
+    }
+}
+
+/// Direction uniformly distributed in the cone towards a sphere of the given
+/// radius, `distance_squared` away along +z
+fn random_to_sphere(radius: f64, distance_squared: f64) -> DVec3 {
+    let mut rng = rand::rng();
+    let r1: f64 = rng.random();
+    let r2: f64 = rng.random();
+    let z = 1.0 + r2 * ((1.0 - radius * radius / distance_squared).max(0.0).sqrt() - 1.0);
+
+    let phi = 2.0 * PI * r1;
+    let x = phi.cos() * (1.0 - z * z).sqrt();
+    let y = phi.sin() * (1.0 - z * z).sqrt();
+
+    DVec3::new(x, y, z)
 }
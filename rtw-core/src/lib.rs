@@ -3,16 +3,26 @@ use thiserror::Error;
 mod scenes;
 
 mod aabb;
+mod animation;
+mod background;
 mod camera;
 mod hittable;
 mod material;
+mod medium;
+mod pdf;
 mod ray;
+mod spectrum;
 mod utils;
 mod texture;
 
 pub struct RenderConfig {
     pub multi_threaded: bool,
     pub output_file: String,
+    /// Number of frames for animated scenes, which use their own default
+    /// when unset
+    pub frames: Option<u32>,
+    /// Trace wavelengths instead of RGB, for dispersion
+    pub spectral: bool,
 }
 
 #[derive(Debug, Error)]
@@ -25,6 +35,30 @@ pub enum RtwError {
     SceneNotFound(String),
     #[error("Io Error: {0}")]
     IoError(String),
+    #[error("Image Error: {0}")]
+    ImageError(String),
+    #[error("Invalid grid resolution({0}x{1})! Need at least 2 samples per axis")]
+    InvalidResolution(usize, usize),
+    #[error("Invalid quad! Edges must not be parallel")]
+    DegenerateQuad,
+    #[error("Animation needs at least one keyframe")]
+    NoKeyframes,
+    #[error("Invalid scale({0})! Scale must be positive")]
+    InvalidScale(f64),
+    #[error("Animation path conflicts with the pose keyframes of the camera")]
+    ConflictingCameraPath,
+    #[error("Invalid density grid bounds! Min must be below max on every axis")]
+    InvalidGridBounds,
+    #[error("Invalid density grid resolution({0}x{1}x{2})! Need at least 2 samples per axis")]
+    InvalidGridResolution(usize, usize, usize),
+    #[error("Density grid needs {0} values but got {1}")]
+    GridSizeMismatch(usize, usize),
+    #[error("Invalid density({1}) at grid sample {0}! Densities must be finite and non-negative")]
+    InvalidDensity(usize, f64),
+    #[error("Invalid MERL BRDF file {0}")]
+    InvalidMerl(String),
+    #[error("Invalid environment map! {0}x{1} texels can't hold {2} values")]
+    InvalidEnvironmentMap(usize, usize, usize),
 }
 
 pub type Result<T> = std::result::Result<T, RtwError>;
@@ -34,6 +68,54 @@ pub fn render_scene(scene: &str, config: &RenderConfig) -> Result<()> {
         crate::scenes::random_scene(config)
     } else if scene == "test" {
         crate::scenes::test_scene(config)
+    } else if scene == "cornell" {
+        crate::scenes::cornell_box(config)
+    } else if scene == "simple_light" {
+        crate::scenes::simple_light(config)
+    } else if scene == "earth" {
+        crate::scenes::earth_scene(config)
+    } else if scene == "metals" {
+        crate::scenes::metals_scene(config)
+    } else if scene == "principled" {
+        crate::scenes::principled_scene(config)
+    } else if scene == "frosted" {
+        crate::scenes::frosted_glass_scene(config)
+    } else if scene == "dispersion" {
+        crate::scenes::dispersion_scene(config)
+    } else if scene == "media" {
+        crate::scenes::media_scene(config)
+    } else if scene == "subsurface" {
+        crate::scenes::subsurface_scene(config)
+    } else if scene == "layered" {
+        crate::scenes::layered_scene(config)
+    } else if scene == "noise" {
+        crate::scenes::noise_scene(config)
+    } else if scene == "sunsky" {
+        crate::scenes::sunsky_scene(config)
+    } else if scene == "environment" {
+        crate::scenes::environment_scene(config)
+    } else if scene == "nodes" {
+        crate::scenes::nodes_scene(config)
+    } else if scene == "mapping" {
+        crate::scenes::mapping_scene(config)
+    } else if scene == "filtering" {
+        crate::scenes::filtering_scene(config)
+    } else if scene == "patterns" {
+        crate::scenes::patterns_scene(config)
+    } else if scene == "measured" {
+        crate::scenes::measured_scene(config)
+    } else if scene == "volumes" {
+        crate::scenes::volumes_scene(config)
+    } else if scene == "bump" {
+        crate::scenes::bump_scene(config)
+    } else if scene == "cutout" {
+        crate::scenes::cutout_scene(config)
+    } else if scene == "motion" {
+        crate::scenes::motion_scene(config)
+    } else if scene == "turntable" {
+        crate::scenes::turntable_scene(config)
+    } else if scene == "terrain" {
+        crate::scenes::terrain_scene(config)
     } else {
         Err(RtwError::SceneNotFound(scene.to_owned()))
     }
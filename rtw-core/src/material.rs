@@ -1,16 +1,120 @@
+pub mod coated;
+pub mod conductor;
 pub mod dielectric;
+pub mod diffuse_light;
 pub mod lambertian;
+pub mod measured;
 pub mod metal;
+pub mod microfacet;
+pub mod mix;
+pub mod normal_map;
+pub mod principled;
+pub mod rough_dielectric;
+pub mod subsurface;
+pub mod volume;
 
 use glam::DVec3;
+use std::sync::{Arc, OnceLock};
 
-use crate::{hittable::HitRecord, ray::Ray};
+use crate::{hittable::HitRecord, medium::Medium, pdf::Pdf, ray::Ray};
 
-pub struct Scatter {
-    pub scattered: Ray,
-    pub attenuation: DVec3,
+pub enum Scatter {
+    /// Single outgoing ray picked by the material, such as a mirror
+    /// reflection. Light sampling can't help here so the ray is followed as is.
+    /// Rays carry their media, so the ray is boxed to keep scatters small.
+    Specular {
+        scattered: Box<Ray>,
+        attenuation: DVec3,
+    },
+    /// Outgoing directions drawn from `pdf` and weighted with
+    /// [`Material::eval`], which lets the renderer also sample lights
+    Pdf(Box<dyn Pdf>),
 }
 
 pub trait Material: Send + Sync {
+    /// Material to shade this hit with instead of this one. Materials made
+    /// of several others pick one of them here, once per hit, so scattering,
+    /// [`Material::eval`] and emission all agree on it.
+    fn select(&self, _hr: &HitRecord) -> Option<Arc<dyn Material>> {
+        None
+    }
+
+    /// Every material [`Material::select`] can pick, so that materials
+    /// wrapping this one can prepare their own version of each
+    fn choices(&self) -> Vec<Arc<dyn Material>> {
+        Vec::new()
+    }
+
     fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter>;
+
+    /// Reflected fraction of light arriving from `direction` towards the
+    /// incoming ray, times the cosine with the normal. Only used for
+    /// [`Scatter::Pdf`] scattering.
+    fn eval(&self, _ray: &Ray, _hr: &HitRecord, _direction: DVec3) -> DVec3 {
+        DVec3::ZERO
+    }
+
+    /// Light given off at a hit towards the incoming ray, nothing for non
+    /// emissive materials
+    fn emitted(&self, _hr: &HitRecord) -> DVec3 {
+        DVec3::ZERO
+    }
+
+    /// Medium filling the inside of closed objects made of this material,
+    /// None for opaque materials
+    fn medium(&self) -> Option<Medium> {
+        None
+    }
+}
+
+/// Copies of a wrapping material, such as a coat, around each material its
+/// base can select. They are made once and shared by every hit, so picking
+/// one doesn't allocate and a medium inside keeps the same identity on the
+/// way in and out.
+#[derive(Default)]
+pub struct Variants(OnceLock<Vec<Variant>>);
+
+/// A material the base can select, and the wrapper around it
+type Variant = (Arc<dyn Material>, Arc<dyn Material>);
+
+impl Variants {
+    /// Wrapped copy of each choice of `base`, paired with the choice
+    fn all(
+        &self,
+        base: &dyn Material,
+        wrap: impl Fn(Arc<dyn Material>) -> Arc<dyn Material>,
+    ) -> &[Variant] {
+        self.0.get_or_init(|| {
+            base.choices()
+                .into_iter()
+                .map(|choice| (choice.clone(), wrap(choice)))
+                .collect()
+        })
+    }
+
+    /// Wrapped copy of whatever `base` selects for this hit
+    pub fn select(
+        &self,
+        base: &dyn Material,
+        hr: &HitRecord,
+        wrap: impl Fn(Arc<dyn Material>) -> Arc<dyn Material>,
+    ) -> Option<Arc<dyn Material>> {
+        let picked = base.select(hr)?;
+        self.all(base, wrap)
+            .iter()
+            .find(|(choice, _)| Arc::ptr_eq(choice, &picked))
+            .map(|(_, wrapped)| wrapped.clone())
+    }
+
+    /// Wrapped copies of every choice of `base`
+    pub fn choices(
+        &self,
+        base: &dyn Material,
+        wrap: impl Fn(Arc<dyn Material>) -> Arc<dyn Material>,
+    ) -> Vec<Arc<dyn Material>> {
+        self.all(base, wrap)
+            .iter()
+            .map(|(_, wrapped)| wrapped.clone())
+            .collect()
+    }
 }
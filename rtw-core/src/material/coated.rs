@@ -0,0 +1,119 @@
+use glam::DVec3;
+use rand::Rng;
+use std::sync::Arc;
+
+use crate::{
+    hittable::HitRecord,
+    material::{Material, Scatter, Variants, microfacet::fresnel_dielectric},
+    medium::Medium,
+    ray::Ray,
+    utils::vec::reflect,
+};
+
+/// Thin smooth dielectric layer, such as varnish or a car's clearcoat, over
+/// any base material.
+///
+/// Light is mirrored off the coat with the Fresnel reflectance of the coat,
+/// the rest reaches the base and is dimmed by the coat again on its way
+/// out. The coat is thin enough that it doesn't bend light on the way.
+pub struct Coated {
+    pub base: Arc<dyn Material>,
+    pub refraction_index: f64,
+    /// Color of the coat seen straight on, light crossing it at an angle
+    /// goes through more of it and gets more tinted
+    pub tint: DVec3,
+    /// Coated copies of the materials a mixed base picks from
+    variants: Variants,
+}
+
+impl Coated {
+    pub fn new(base: Arc<dyn Material>, refraction_index: f64) -> Self {
+        Self {
+            base,
+            refraction_index,
+            tint: DVec3::ONE,
+            variants: Variants::default(),
+        }
+    }
+
+    pub fn tint(mut self, tint: DVec3) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Fraction of light leaving the base towards `cos_in` that makes it
+    /// through the coat, after reaching the base from `cos_out`. Both are
+    /// cosines with the normal. The Fresnel loss on the way to the base is
+    /// left out, see [`Coated::scatter`].
+    fn transmittance(&self, cos_in: f64, cos_out: f64) -> DVec3 {
+        let (cos_in, cos_out) = (cos_in.abs().max(1e-4), cos_out.abs().max(1e-4));
+        // Half of the tint on the way in and half on the way out when seen
+        // straight on
+        let path = 0.5 * (1.0 / cos_in + 1.0 / cos_out);
+        let absorbed = self.tint.clamp(DVec3::splat(1e-6), DVec3::ONE).powf(path);
+        absorbed * (1.0 - fresnel_dielectric(cos_in, self.refraction_index))
+    }
+
+    /// Same coat over another base
+    fn around(&self, base: Arc<dyn Material>) -> Arc<dyn Material> {
+        Arc::new(Self::new(base, self.refraction_index).tint(self.tint))
+    }
+}
+
+impl Material for Coated {
+    /// Coats the material picked by a mixed base
+    fn select(&self, hr: &HitRecord) -> Option<Arc<dyn Material>> {
+        self.variants
+            .select(self.base.as_ref(), hr, |base| self.around(base))
+    }
+
+    fn choices(&self) -> Vec<Arc<dyn Material>> {
+        self.variants
+            .choices(self.base.as_ref(), |base| self.around(base))
+    }
+
+    /// The coat reflection is picked with a chance equal to its Fresnel
+    /// reflectance, so the base lobe sampled otherwise needs no extra weight
+    /// for the light first getting through the coat.
+    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
+        let unit_direction = ray.direction.normalize();
+        let cos_out = hr.normal.dot(-unit_direction);
+        let fresnel = fresnel_dielectric(cos_out, self.refraction_index);
+        if rand::rng().random::<f64>() < fresnel {
+            return Some(Scatter::Specular {
+                scattered: Box::new(ray.scattered(hr.point, reflect(unit_direction, hr.normal))),
+                attenuation: DVec3::ONE,
+            });
+        }
+
+        match self.base.scatter(ray, hr)? {
+            Scatter::Specular {
+                scattered,
+                attenuation,
+            } => {
+                let cos_in = hr.normal.dot(scattered.direction.normalize());
+                Some(Scatter::Specular {
+                    scattered,
+                    attenuation: attenuation * self.transmittance(cos_in, cos_out),
+                })
+            }
+            pdf => Some(pdf),
+        }
+    }
+
+    /// Base part of the material only, the coat is a mirror and picked
+    /// separately in `scatter`
+    fn eval(&self, ray: &Ray, hr: &HitRecord, direction: DVec3) -> DVec3 {
+        let cos_out = hr.normal.dot(-ray.direction.normalize());
+        let cos_in = hr.normal.dot(direction.normalize());
+        self.base.eval(ray, hr, direction) * self.transmittance(cos_in, cos_out)
+    }
+
+    fn emitted(&self, hr: &HitRecord) -> DVec3 {
+        self.base.emitted(hr)
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        self.base.medium()
+    }
+}
@@ -0,0 +1,119 @@
+use glam::DVec3;
+
+use crate::{
+    hittable::HitRecord,
+    material::{
+        Material, Scatter,
+        microfacet::{Ggx, GgxReflectionPdf, fresnel_conductor},
+    },
+    ray::Ray,
+    utils::{onb::Onb, vec::reflect},
+};
+
+/// Below this roughness the surface is treated as a perfect mirror
+const SMOOTH_ROUGHNESS: f64 = 1e-3;
+
+/// Metal with a GGX microfacet surface and exact conductor Fresnel.
+///
+/// The complex index of refraction `eta + i k` is given per color channel
+/// (red, green, blue), see the presets for common metals.
+pub struct Conductor {
+    pub eta: DVec3,
+    pub k: DVec3,
+    pub roughness: f64,
+    /// Stretches highlights along one tangent direction, in [-1, 1]
+    pub anisotropy: f64,
+}
+
+impl Conductor {
+    pub fn new(eta: DVec3, k: DVec3, roughness: f64, anisotropy: f64) -> Self {
+        Self {
+            eta,
+            k,
+            roughness,
+            anisotropy,
+        }
+    }
+
+    pub fn gold(roughness: f64) -> Self {
+        Self::new(
+            DVec3::new(0.143, 0.374, 1.442),
+            DVec3::new(3.983, 2.385, 1.603),
+            roughness,
+            0.0,
+        )
+    }
+
+    pub fn copper(roughness: f64) -> Self {
+        Self::new(
+            DVec3::new(0.200, 0.924, 1.102),
+            DVec3::new(3.912, 2.452, 2.142),
+            roughness,
+            0.0,
+        )
+    }
+
+    pub fn aluminium(roughness: f64) -> Self {
+        Self::new(
+            DVec3::new(1.657, 0.880, 0.521),
+            DVec3::new(9.224, 6.270, 4.837),
+            roughness,
+            0.0,
+        )
+    }
+
+    pub fn silver(roughness: f64) -> Self {
+        Self::new(
+            DVec3::new(0.155, 0.117, 0.138),
+            DVec3::new(4.828, 3.122, 2.147),
+            roughness,
+            0.0,
+        )
+    }
+
+    pub fn anisotropy(mut self, anisotropy: f64) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    fn distribution(&self) -> Ggx {
+        Ggx::from_roughness(self.roughness, self.anisotropy)
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
+        let wo = -ray.direction.normalize();
+
+        if self.roughness < SMOOTH_ROUGHNESS {
+            let cos_i = hr.normal.dot(wo).clamp(0.0, 1.0);
+            return Some(Scatter::Specular {
+                scattered: Box::new(ray.scattered(hr.point, reflect(-wo, hr.normal))),
+                attenuation: fresnel_conductor(cos_i, self.eta, self.k),
+            });
+        }
+
+        Some(Scatter::Pdf(Box::new(GgxReflectionPdf::new(
+            Onb::from_tangent(hr.normal, hr.dpdu),
+            wo,
+            self.distribution(),
+        ))))
+    }
+
+    fn eval(&self, ray: &Ray, hr: &HitRecord, direction: DVec3) -> DVec3 {
+        // Anisotropic roughness stretches along the surface tangent
+        let uvw = Onb::from_tangent(hr.normal, hr.dpdu);
+        let wo = uvw.local(-ray.direction.normalize());
+        let wi = uvw.local(direction.normalize());
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return DVec3::ZERO;
+        }
+
+        let ggx = self.distribution();
+        let h = (wo + wi).normalize();
+        let fresnel = fresnel_conductor(wo.dot(h), self.eta, self.k);
+
+        // f * cos(wi) = F D G / (4 cos(wo) cos(wi)) * cos(wi)
+        fresnel * ggx.d(h) * ggx.g2(wo, wi) / (4.0 * wo.z)
+    }
+}
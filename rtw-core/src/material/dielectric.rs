@@ -1,14 +1,133 @@
 use glam::DVec3;
+use std::sync::Arc;
 
 use crate::{
     hittable::HitRecord,
     material::{Material, Scatter},
+    medium::{Medium, density::Density, medium_id},
     ray::Ray,
+    spectrum::LAMBDA_D,
     utils::vec::{reflect, refract},
 };
 
+/// Refraction index varying with wavelength, which splits white light into
+/// its colors
+#[derive(Debug, Clone, Copy)]
+pub enum Dispersion {
+    /// `n = a + b / lambda^2`, with lambda in micrometers
+    Cauchy { a: f64, b: f64 },
+    /// `n^2 = 1 + sum(b_i lambda^2 / (lambda^2 - c_i))`, with lambda in
+    /// micrometers and `c` in square micrometers
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// Borosilicate crown glass, the common optical glass
+    pub fn bk7() -> Self {
+        Self::Sellmeier {
+            b: [1.03961212, 0.231792344, 1.01046945],
+            c: [0.00600069867, 0.0200179144, 103.560653],
+        }
+    }
+
+    pub fn diamond() -> Self {
+        Self::Sellmeier {
+            b: [0.3306, 4.3356, 0.0],
+            c: [0.030625, 0.011236, 0.0],
+        }
+    }
+
+    /// Refraction index at `wavelength` in nanometers
+    pub fn index(&self, wavelength: f64) -> f64 {
+        let lambda = wavelength / 1000.0;
+        let lambda2 = lambda * lambda;
+        match self {
+            Dispersion::Cauchy { a, b } => a + b / lambda2,
+            Dispersion::Sellmeier { b, c } => (1.0
+                + b.iter()
+                    .zip(c)
+                    .map(|(b, c)| b * lambda2 / (lambda2 - c))
+                    .sum::<f64>())
+            .sqrt(),
+        }
+    }
+}
+
+/// Smooth glass or liquid. Closed objects made of it are filled with a
+/// [`Medium`], which can absorb light and nest inside other media.
 pub struct Dielectric {
+    /// Index used in RGB mode, and at every wavelength without dispersion
     pub refraction_index: f64,
+    pub dispersion: Option<Dispersion>,
+    /// Fraction of light absorbed per unit distance inside, per color
+    /// channel
+    pub absorption: DVec3,
+    /// Fraction of light scattered off course per unit distance inside, per
+    /// color channel
+    pub scattering: DVec3,
+    /// Varying density inside, see [`Medium::density`]
+    pub density: Option<Arc<dyn Density>>,
+    /// Scattering asymmetry inside, see [`Medium::anisotropy`]
+    pub anisotropy: f64,
+    /// Nesting priority, see [`Medium::priority`]
+    pub priority: u32,
+}
+
+impl Dielectric {
+    pub fn new(refraction_index: f64) -> Self {
+        Self {
+            refraction_index,
+            dispersion: None,
+            absorption: DVec3::ZERO,
+            scattering: DVec3::ZERO,
+            density: None,
+            anisotropy: 0.0,
+            priority: 0,
+        }
+    }
+
+    /// Glass whose index depends on the wavelength in spectral mode. RGB
+    /// rendering uses the index at the sodium d-line.
+    pub fn dispersive(dispersion: Dispersion) -> Self {
+        Self {
+            dispersion: Some(dispersion),
+            ..Self::new(dispersion.index(LAMBDA_D))
+        }
+    }
+
+    pub fn absorption(mut self, absorption: DVec3) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Absorption giving `color` after travelling `distance` inside
+    pub fn tint(self, color: DVec3, distance: f64) -> Self {
+        let absorption = -color.max(DVec3::splat(1e-6)).map(f64::ln) / distance;
+        self.absorption(absorption)
+    }
+
+    pub fn scattering(mut self, scattering: DVec3) -> Self {
+        self.scattering = scattering;
+        self
+    }
+
+    pub fn priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    fn medium_inside(&self) -> Medium {
+        Medium {
+            refraction_index: self.refraction_index,
+            dispersion: self.dispersion,
+            absorption: self.absorption,
+            scattering: self.scattering,
+            density: self.density.clone(),
+            anisotropy: self.anisotropy,
+            priority: self.priority,
+            index_matched: false,
+        }
+    }
 }
 
 fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
@@ -21,15 +140,28 @@ fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
 impl Material for Dielectric {
     fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
         let attenuation = DVec3::ONE;
-        let ri = if hr.front_face {
-            1.0 / self.refraction_index
-        } else {
-            self.refraction_index
+        let inside = self.medium_inside();
+        let (near, far) = ray
+            .media
+            .sides(medium_id(&hr.material), &inside, hr.front_face);
+
+        // Each wavelength bends differently, so only the hero is followed
+        let dispersive = [near, far]
+            .iter()
+            .flatten()
+            .any(|medium| medium.dispersion.is_some());
+        let (hero, wavelengths) = match ray.wavelengths {
+            Some(wavelengths) if dispersive => {
+                (Some(wavelengths.hero()), Some(wavelengths.hero_only()))
+            }
+            wavelengths => (None, wavelengths),
         };
+        let index = |medium: Option<&Medium>| medium.map_or(1.0, |medium| medium.index(hero));
+        let ri = index(near) / index(far);
 
         let unit_direction = ray.direction.normalize();
         let cos_theta = f64::min(1.0, hr.normal.dot(-unit_direction));
-        let sin_theta = (1.0 - cos_theta).sqrt();
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
         let cannot_refract = ri * sin_theta > 1.0;
         let direction = if cannot_refract || reflectance(cos_theta, ri) > rand::random() {
             reflect(unit_direction, hr.normal)
@@ -37,11 +169,15 @@ impl Material for Dielectric {
             refract(unit_direction, hr.normal, ri)
         };
 
-        let scattered = Ray::with_time(hr.point, direction, ray.time);
+        let scattered = Box::new(ray.scattered(hr.point, direction).wavelengths(wavelengths));
 
-        Some(Scatter {
+        Some(Scatter::Specular {
             scattered,
             attenuation,
         })
     }
+
+    fn medium(&self) -> Option<Medium> {
+        Some(self.medium_inside())
+    }
 }
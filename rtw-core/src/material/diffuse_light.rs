@@ -0,0 +1,48 @@
+use crate::{
+    hittable::HitRecord,
+    material::{Material, Scatter},
+    ray::Ray,
+    texture::{Texture, solid::SolidColor},
+};
+use glam::DVec3;
+use std::sync::Arc;
+
+/// Emits light from the texture in all directions and reflects nothing
+pub struct DiffuseLight {
+    pub texture: Arc<dyn Texture>,
+    /// Shine from the front face only
+    one_sided: bool,
+}
+
+impl DiffuseLight {
+    pub fn from_color(emit: DVec3) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(emit)))
+    }
+
+    pub fn from_texture(texture: Arc<dyn Texture>) -> Self {
+        Self {
+            texture,
+            one_sided: false,
+        }
+    }
+
+    /// Dark from behind, for lights mounted against a wall or ceiling that
+    /// would otherwise brightly light up the surface they sit on
+    pub fn one_sided(mut self) -> Self {
+        self.one_sided = true;
+        self
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _ray: &Ray, _hr: &HitRecord) -> Option<Scatter> {
+        None
+    }
+
+    fn emitted(&self, hr: &HitRecord) -> DVec3 {
+        if self.one_sided && !hr.front_face {
+            return DVec3::ZERO;
+        }
+        self.texture.value(hr.u, hr.v, hr.point)
+    }
+}
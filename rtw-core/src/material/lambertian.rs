@@ -1,12 +1,12 @@
 use crate::{
     hittable::HitRecord,
     material::{Material, Scatter},
+    pdf::CosinePdf,
     ray::Ray,
     texture::{Texture, solid::SolidColor},
-    utils::vec::random_unit_vector,
 };
 use glam::DVec3;
-use std::sync::Arc;
+use std::{f64::consts::PI, sync::Arc};
 
 pub struct Lambertian {
     pub texture: Arc<dyn Texture>,
@@ -25,18 +25,12 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
-        let mut scatter_direction = hr.normal + random_unit_vector();
-        if scatter_direction.abs_diff_eq(DVec3::ZERO, 1e-8) {
-            scatter_direction = hr.normal;
-        }
-
-        let attenuation = self.texture.value(hr.u, hr.v, hr.point);
-        let scattered = Ray::with_time(hr.point, scatter_direction, ray.time);
+    fn scatter(&self, _ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
+        Some(Scatter::Pdf(Box::new(CosinePdf::new(hr.normal))))
+    }
 
-        Some(Scatter {
-            scattered,
-            attenuation,
-        })
+    fn eval(&self, _ray: &Ray, hr: &HitRecord, direction: DVec3) -> DVec3 {
+        let cosine = hr.normal.dot(direction.normalize()).max(0.0);
+        self.texture.value_at(hr) * cosine / PI
     }
 }
@@ -0,0 +1,250 @@
+use glam::{DQuat, DVec3};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::{
+    Result, RtwError,
+    hittable::HitRecord,
+    material::{
+        Material, Scatter,
+        microfacet::{Ggx, GgxReflectionPdf},
+    },
+    pdf::{CosinePdf, MixturePdf},
+    ray::Ray,
+    utils::onb::Onb,
+};
+
+/// Table resolution of the MERL format, in theta half, theta difference and
+/// phi difference
+const THETA_H_RES: usize = 90;
+const THETA_D_RES: usize = 90;
+const PHI_D_RES: usize = 180;
+const TABLE_SIZE: usize = THETA_H_RES * THETA_D_RES * PHI_D_RES;
+
+/// Factors turning the stored values into reflectance, per color channel
+const SCALE: [f64; 3] = [1.0 / 1500.0, 1.15 / 1500.0, 1.66 / 1500.0];
+
+/// Isotropic BRDF measured from a real material, as published in the MERL
+/// database of Matusik et al. 2003 "A Data-Driven Reflectance Model".
+///
+/// Reflectance is looked up in a table indexed by the half vector and
+/// difference angles of Rusinkiewicz 1998. Directions are sampled from a
+/// mix of a cosine lobe and a GGX lobe roughly fitted to the table, and
+/// weighted by the tabulated value, so the fit only affects noise.
+pub struct Measured {
+    /// Reflectance per table entry
+    table: Vec<[f32; 3]>,
+    /// GGX lobe matching the specular peak
+    ggx: Ggx,
+    /// Chance of sampling the GGX lobe instead of the cosine lobe
+    specular_weight: f64,
+}
+
+impl Measured {
+    /// Reads a MERL `.binary` file: three little endian 32 bit integers
+    /// giving the table dimensions, followed by the red, green and blue
+    /// tables as little endian 64 bit floats. The tables are not shipped
+    /// with the renderer and can be downloaded from the MERL BRDF database
+    /// at <https://www.merl.com/brdf/>.
+    pub fn open_merl(path: &str) -> Result<Self> {
+        let bytes = std::fs::read(path).map_err(|err| {
+            RtwError::IoError(format!(
+                "{path}: {err}, MERL tables can be downloaded from https://www.merl.com/brdf/"
+            ))
+        })?;
+        let invalid = |reason: &str| RtwError::InvalidMerl(format!("{path}: {reason}"));
+
+        let (header, data) = bytes
+            .split_at_checked(12)
+            .ok_or_else(|| invalid("missing header"))?;
+        let dims = header
+            .chunks_exact(4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as i64)
+            .collect::<Vec<_>>();
+        if dims != [THETA_H_RES as i64, THETA_D_RES as i64, PHI_D_RES as i64] {
+            return Err(invalid("unexpected table dimensions"));
+        }
+        if data.len() != 3 * TABLE_SIZE * 8 {
+            return Err(invalid("unexpected table size"));
+        }
+
+        let value = |i: usize| {
+            let b = &data[i * 8..i * 8 + 8];
+            f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+        };
+        // Missing measurements are stored as negative values
+        let table = (0..TABLE_SIZE)
+            .map(|i| {
+                std::array::from_fn(|c| (value(i + c * TABLE_SIZE) * SCALE[c]).max(0.0) as f32)
+            })
+            .collect::<Vec<_>>();
+
+        let (ggx, specular_weight) = fit_lobe(&table);
+        Ok(Self {
+            table,
+            ggx,
+            specular_weight,
+        })
+    }
+
+    /// Reflectance for directions `wo` and `wi` in the local frame of the
+    /// surface
+    fn lookup(&self, wo: DVec3, wi: DVec3) -> DVec3 {
+        let h = (wo + wi).normalize();
+        let theta_h = h.z.clamp(-1.0, 1.0).acos();
+        let phi_h = h.y.atan2(h.x);
+
+        // Incoming direction seen from the half vector
+        let to_half = DQuat::from_rotation_y(-theta_h) * DQuat::from_rotation_z(-phi_h);
+        let diff = to_half * wi;
+        let theta_d = diff.z.clamp(-1.0, 1.0).acos();
+        let phi_d = diff.y.atan2(diff.x);
+
+        let [r, g, b] = self.table[index(theta_h, theta_d, phi_d)];
+        DVec3::new(r as f64, g as f64, b as f64)
+    }
+}
+
+/// Table entry for the given half and difference angles. Theta half is
+/// stored non-linearly to fit the specular peak in more entries.
+fn index(theta_h: f64, theta_d: f64, phi_d: f64) -> usize {
+    let theta_h_index = ((theta_h.max(0.0) / FRAC_PI_2).sqrt() * THETA_H_RES as f64) as usize;
+    let theta_d_index = (theta_d / FRAC_PI_2 * THETA_D_RES as f64) as usize;
+    // Reciprocity makes phi_d and phi_d + pi equal
+    let phi_d = if phi_d < 0.0 { phi_d + PI } else { phi_d };
+    let phi_d_index = (phi_d / PI * PHI_D_RES as f64) as usize;
+
+    let theta_h_index = theta_h_index.min(THETA_H_RES - 1);
+    let theta_d_index = theta_d_index.min(THETA_D_RES - 1);
+    let phi_d_index = phi_d_index.min(PHI_D_RES - 1);
+    (theta_h_index * THETA_D_RES + theta_d_index) * PHI_D_RES + phi_d_index
+}
+
+/// Rough GGX fit of the specular peak, from how fast reflectance falls off
+/// with theta half near normal incidence, and the share of reflected light
+/// it carries against the diffuse floor
+fn fit_lobe(table: &[[f32; 3]]) -> (Ggx, f64) {
+    let luminance = |[r, g, b]: [f32; 3]| 0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64;
+    let profile = (0..THETA_H_RES)
+        .map(|i| luminance(table[(i * THETA_D_RES + 1) * PHI_D_RES + PHI_D_RES / 2]))
+        .collect::<Vec<_>>();
+    let peak = profile[0];
+    let floor = profile.iter().copied().fold(f64::INFINITY, f64::min);
+
+    let half = floor + 0.5 * (peak - floor);
+    let half_index = profile
+        .iter()
+        .position(|&f| f < half)
+        .unwrap_or(THETA_H_RES);
+    let theta_h = (half_index as f64 / THETA_H_RES as f64).powi(2) * FRAC_PI_2;
+    // GGX falls to half its peak where tan^2 = alpha^2 (sqrt(2) - 1)
+    let alpha = (theta_h.tan() / (2f64.sqrt() - 1.0).sqrt()).clamp(0.01, 1.0);
+
+    // At normal incidence the GGX lobe peaks at F / (4 pi alpha^2)
+    let specular = (4.0 * PI * alpha * alpha * (peak - floor)).min(1.0);
+    let diffuse = PI * floor;
+    let weight = if specular + diffuse > 0.0 {
+        (specular / (specular + diffuse)).clamp(0.1, 0.9)
+    } else {
+        0.5
+    };
+    (
+        Ggx {
+            alpha_x: alpha,
+            alpha_y: alpha,
+        },
+        weight,
+    )
+}
+
+impl Material for Measured {
+    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
+        let wo = -ray.direction.normalize();
+        Some(Scatter::Pdf(Box::new(MixturePdf::new(vec![
+            (
+                1.0 - self.specular_weight,
+                Box::new(CosinePdf::new(hr.normal)),
+            ),
+            (
+                self.specular_weight,
+                Box::new(GgxReflectionPdf::new(Onb::new(hr.normal), wo, self.ggx)),
+            ),
+        ]))))
+    }
+
+    fn eval(&self, ray: &Ray, hr: &HitRecord, direction: DVec3) -> DVec3 {
+        let uvw = Onb::new(hr.normal);
+        let wo = uvw.local(-ray.direction.normalize());
+        let wi = uvw.local(direction.normalize());
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return DVec3::ZERO;
+        }
+        self.lookup(wo, wi) * wi.z
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Table where every entry holds its own index, exact in f32 for the
+    /// size of the table
+    fn numbered() -> Measured {
+        Measured {
+            table: (0..TABLE_SIZE).map(|i| [i as f32; 3]).collect(),
+            ggx: Ggx {
+                alpha_x: 0.1,
+                alpha_y: 0.1,
+            },
+            specular_weight: 0.5,
+        }
+    }
+
+    /// Expected indices follow `theta_half_index`, `theta_diff_index` and
+    /// `phi_diff_index` of the reference MERL reader
+    #[test]
+    fn index_matches_reference() {
+        let deg = |d: f64| d.to_radians();
+        assert_eq!(index(0.0, 0.0, 0.0), 0);
+        // Below the first bin clamps to it
+        assert_eq!(index(-0.1, 0.0, 0.0), 0);
+        // theta_h = (10.5 / 90)^2 * 90 degrees lands in bin 10
+        let theta_h = (10.5f64 / 90.0).powi(2) * FRAC_PI_2;
+        assert_eq!(
+            index(theta_h, deg(30.5), deg(60.5)),
+            (10 * 90 + 30) * 180 + 60
+        );
+        // Negative phi_d wraps by pi
+        assert_eq!(
+            index(theta_h, deg(30.5), deg(-45.5)),
+            (10 * 90 + 30) * 180 + 134
+        );
+        // Grazing angles clamp to the last entry
+        assert_eq!(index(FRAC_PI_2, FRAC_PI_2, PI), TABLE_SIZE - 1);
+    }
+
+    #[test]
+    fn lookup_uses_half_and_difference_angles() {
+        let measured = numbered();
+        let at = |wo: DVec3, wi: DVec3| measured.lookup(wo, wi).x as usize;
+
+        // Normal incidence
+        assert_eq!(at(DVec3::Z, DVec3::Z), 0);
+
+        // Directions built from known half and difference angles, mirrored
+        // about the half vector
+        let (theta_h, theta_d, phi_d) = (
+            (10.5f64 / 90.0).powi(2) * FRAC_PI_2,
+            30.5f64.to_radians(),
+            60.5f64.to_radians(),
+        );
+        let diff = DVec3::new(
+            theta_d.sin() * phi_d.cos(),
+            theta_d.sin() * phi_d.sin(),
+            theta_d.cos(),
+        );
+        let wi = DQuat::from_rotation_y(theta_h) * diff;
+        let h = DQuat::from_rotation_y(theta_h) * DVec3::Z;
+        let wo = 2.0 * h.dot(wi) * h - wi;
+        assert_eq!(at(wo, wi), (10 * 90 + 30) * 180 + 60);
+    }
+}
@@ -17,10 +17,10 @@ impl Material for Metal {
     fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
         let reflected =
             reflect(ray.direction, hr.normal).normalize() + self.fuzz * random_unit_vector();
-        let scattered = Ray::with_time(hr.point, reflected, ray.time);
+        let scattered = Box::new(ray.scattered(hr.point, reflected));
         let attenuation = self.albedo;
         if scattered.direction.dot(hr.normal) > 0.0 {
-            Some(Scatter {
+            Some(Scatter::Specular {
                 scattered,
                 attenuation,
             })
@@ -0,0 +1,236 @@
+use glam::DVec3;
+use rand::Rng;
+use std::f64::consts::PI;
+
+use crate::{
+    pdf::Pdf,
+    utils::{onb::Onb, vec::reflect},
+};
+
+/// Trowbridge-Reitz (GGX) distribution of microfacet normals, in a local
+/// frame where the macro surface normal is +z.
+///
+/// `alpha_x` and `alpha_y` are the roughness along the local x and y axes,
+/// equal for isotropic surfaces.
+#[derive(Debug, Clone, Copy)]
+pub struct Ggx {
+    pub alpha_x: f64,
+    pub alpha_y: f64,
+}
+
+impl Ggx {
+    /// Maps the perceptual `roughness` in [0, 1] and `anisotropy` in [-1, 1]
+    /// to alphas, stretching highlights along x for positive anisotropy.
+    pub fn from_roughness(roughness: f64, anisotropy: f64) -> Self {
+        let alpha = roughness.clamp(0.0, 1.0).powi(2).max(1e-4);
+        let aspect = (1.0 - 0.9 * anisotropy.clamp(-1.0, 1.0)).sqrt();
+        Self {
+            alpha_x: (alpha / aspect).max(1e-4),
+            alpha_y: (alpha * aspect).max(1e-4),
+        }
+    }
+
+    /// Density of microfacets with normal `h`
+    pub fn d(&self, h: DVec3) -> f64 {
+        if h.z <= 0.0 {
+            return 0.0;
+        }
+        let e = (h.x / self.alpha_x).powi(2) + (h.y / self.alpha_y).powi(2) + h.z * h.z;
+        1.0 / (PI * self.alpha_x * self.alpha_y * e * e)
+    }
+
+    /// Smith auxiliary function for direction `w`
+    fn lambda(&self, w: DVec3) -> f64 {
+        if w.z.abs() < 1e-12 {
+            return f64::INFINITY;
+        }
+        let tan2 = ((self.alpha_x * w.x).powi(2) + (self.alpha_y * w.y).powi(2)) / (w.z * w.z);
+        0.5 * ((1.0 + tan2).sqrt() - 1.0)
+    }
+
+    /// Fraction of microfacets visible from `w`
+    pub fn g1(&self, w: DVec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// Height correlated masking-shadowing between `wo` and `wi`
+    pub fn g2(&self, wo: DVec3, wi: DVec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Microfacet normal visible from `wo`, following Heitz 2018
+    /// "Sampling the GGX Distribution of Visible Normals"
+    pub fn sample_visible_normal(&self, wo: DVec3) -> DVec3 {
+        let mut rng = rand::rng();
+        let (u1, u2): (f64, f64) = (rng.random(), rng.random());
+
+        // Stretch the view direction to the hemisphere configuration
+        let vh = DVec3::new(self.alpha_x * wo.x, self.alpha_y * wo.y, wo.z).normalize();
+        let len2 = vh.x * vh.x + vh.y * vh.y;
+        let t1 = if len2 > 0.0 {
+            DVec3::new(-vh.y, vh.x, 0.0) / len2.sqrt()
+        } else {
+            DVec3::X
+        };
+        let t2 = vh.cross(t1);
+
+        // Uniform disk sample, warped towards the visible half of the disk
+        let r = u1.sqrt();
+        let phi = 2.0 * PI * u2;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + vh.z);
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let nh = p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * vh;
+
+        // Unstretch back to the ellipsoid configuration
+        DVec3::new(self.alpha_x * nh.x, self.alpha_y * nh.y, nh.z.max(0.0)).normalize()
+    }
+
+    /// Density of `sample_visible_normal` picking the normal `h`
+    pub fn visible_normal_pdf(&self, wo: DVec3, h: DVec3) -> f64 {
+        if wo.z <= 0.0 {
+            return 0.0;
+        }
+        self.g1(wo) * wo.dot(h).max(0.0) * self.d(h) / wo.z
+    }
+
+    /// Density of reflecting `wo` into `wi` through a visible normal sample
+    pub fn reflection_pdf(&self, wo: DVec3, wi: DVec3) -> f64 {
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return 0.0;
+        }
+        let h = (wo + wi).normalize();
+        self.g1(wo) * self.d(h) / (4.0 * wo.z)
+    }
+}
+
+/// Reflection directions through visible GGX normals
+pub struct GgxReflectionPdf {
+    uvw: Onb,
+    /// Direction towards the viewer in the local frame
+    wo: DVec3,
+    ggx: Ggx,
+}
+
+impl GgxReflectionPdf {
+    /// Reflections about the `w` axis of `uvw`, which also lines up the
+    /// stretch of anisotropic distributions with its `u` axis
+    pub fn new(uvw: Onb, wo: DVec3, ggx: Ggx) -> Self {
+        Self {
+            uvw,
+            wo: uvw.local(wo),
+            ggx,
+        }
+    }
+}
+
+impl Pdf for GgxReflectionPdf {
+    fn value(&self, direction: DVec3) -> f64 {
+        let wi = self.uvw.local(direction.normalize());
+        self.ggx.reflection_pdf(self.wo, wi)
+    }
+
+    fn generate(&self) -> DVec3 {
+        let h = self.ggx.sample_visible_normal(self.wo);
+        self.uvw.transform(reflect(-self.wo, h))
+    }
+}
+
+/// Unpolarized Fresnel reflectance of a conductor with complex index of
+/// refraction `eta + i k`, evaluated per color channel
+pub fn fresnel_conductor(cos_i: f64, eta: DVec3, k: DVec3) -> DVec3 {
+    let channel = |eta: f64, k: f64| {
+        let cos2 = cos_i * cos_i;
+        let sin2 = 1.0 - cos2;
+        let t0 = eta * eta - k * k - sin2;
+        let a2b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+        let a = (0.5 * (a2b2 + t0)).max(0.0).sqrt();
+
+        let t1 = a2b2 + cos2;
+        let t2 = 2.0 * cos_i * a;
+        let rs = (t1 - t2) / (t1 + t2);
+
+        let t3 = cos2 * a2b2 + sin2 * sin2;
+        let t4 = t2 * sin2;
+        let rp = rs * (t3 - t4) / (t3 + t4);
+
+        0.5 * (rp + rs)
+    };
+
+    DVec3::new(
+        channel(eta.x, k.x),
+        channel(eta.y, k.y),
+        channel(eta.z, k.z),
+    )
+}
+
+/// Unpolarized Fresnel reflectance of a dielectric interface, where `eta`
+/// is the index on the far side over the index on the incident side. Total
+/// internal reflection gives 1.
+pub fn fresnel_dielectric(cos_i: f64, eta: f64) -> f64 {
+    let cos_i = cos_i.clamp(0.0, 1.0);
+    let sin2_t = (1.0 - cos_i * cos_i) / (eta * eta);
+    if sin2_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+
+    let r_parallel = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let r_perpendicular = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    0.5 * (r_parallel * r_parallel + r_perpendicular * r_perpendicular)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflection_pdf_matches_reflected_samples() {
+        let ggx = Ggx::from_roughness(0.7, 0.6);
+        let wo = DVec3::new(0.8, 0.3, 0.3).normalize();
+
+        // Directions above the surface, binned by polar and azimuth angle
+        let (n_theta, n_phi) = (4, 4);
+        let bin = |w: DVec3| {
+            let theta = w.z.clamp(0.0, 1.0).acos() / (0.5 * PI);
+            let phi = w.y.atan2(w.x).rem_euclid(2.0 * PI) / (2.0 * PI);
+            let i = ((theta * n_theta as f64) as usize).min(n_theta - 1);
+            let j = ((phi * n_phi as f64) as usize).min(n_phi - 1);
+            i * n_phi + j
+        };
+
+        let samples = 200_000;
+        let mut sampled = vec![0.0; n_theta * n_phi];
+        for _ in 0..samples {
+            let h = ggx.sample_visible_normal(wo);
+            let wi = 2.0 * wo.dot(h) * h - wo;
+            if wi.z > 0.0 {
+                sampled[bin(wi)] += 1.0 / samples as f64;
+            }
+        }
+
+        // Midpoint rule over the hemisphere
+        let (steps_theta, steps_phi) = (400, 800);
+        let (d_theta, d_phi) = (0.5 * PI / steps_theta as f64, 2.0 * PI / steps_phi as f64);
+        let mut integrated = vec![0.0; n_theta * n_phi];
+        for i in 0..steps_theta {
+            let theta = (i as f64 + 0.5) * d_theta;
+            for j in 0..steps_phi {
+                let phi = (j as f64 + 0.5) * d_phi;
+                let wi = DVec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
+                integrated[bin(wi)] += ggx.reflection_pdf(wo, wi) * theta.sin() * d_theta * d_phi;
+            }
+        }
+
+        for (sampled, integrated) in sampled.iter().zip(&integrated) {
+            assert!(
+                (sampled - integrated).abs() < 5e-3,
+                "{sampled} sampled, {integrated} integrated"
+            );
+        }
+    }
+}
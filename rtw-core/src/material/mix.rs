@@ -0,0 +1,52 @@
+use glam::DVec3;
+use rand::Rng;
+use std::sync::Arc;
+
+use crate::{
+    hittable::HitRecord,
+    material::{Material, Scatter},
+    ray::Ray,
+    texture::{Texture, solid::SolidColor},
+};
+
+/// Blend of two materials, such as rust patches on metal.
+///
+/// Each hit picks one of the two at random, `b` with a chance given by the
+/// first channel of `weight` and `a` otherwise, which averages out to the
+/// weighted mix of both.
+pub struct MixMaterial {
+    pub a: Arc<dyn Material>,
+    pub b: Arc<dyn Material>,
+    pub weight: Arc<dyn Texture>,
+}
+
+impl MixMaterial {
+    pub fn new(a: Arc<dyn Material>, b: Arc<dyn Material>, weight: Arc<dyn Texture>) -> Self {
+        Self { a, b, weight }
+    }
+
+    /// Same blend everywhere
+    pub fn uniform(a: Arc<dyn Material>, b: Arc<dyn Material>, weight: f64) -> Self {
+        Self::new(a, b, Arc::new(SolidColor::new(DVec3::splat(weight))))
+    }
+}
+
+impl Material for MixMaterial {
+    fn select(&self, hr: &HitRecord) -> Option<Arc<dyn Material>> {
+        let weight = self.weight.value_at(hr).x;
+        if rand::rng().random::<f64>() < weight {
+            Some(self.b.clone())
+        } else {
+            Some(self.a.clone())
+        }
+    }
+
+    fn choices(&self) -> Vec<Arc<dyn Material>> {
+        vec![self.a.clone(), self.b.clone()]
+    }
+
+    /// Never called, hits are shaded with the selected material
+    fn scatter(&self, _ray: &Ray, _hr: &HitRecord) -> Option<Scatter> {
+        None
+    }
+}
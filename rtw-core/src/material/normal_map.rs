@@ -0,0 +1,134 @@
+use glam::DVec3;
+use std::sync::Arc;
+
+use crate::{
+    hittable::HitRecord,
+    material::{Material, Scatter, Variants},
+    medium::Medium,
+    ray::Ray,
+    texture::Texture,
+};
+
+/// Step in (u, v) used to find the slope of a bump map
+const BUMP_DELTA: f64 = 1e-4;
+
+/// How the shading normal is tilted away from the surface normal
+pub enum Perturbation {
+    /// Tangent space normal map, with red along `dpdu`, green along `dpdv`
+    /// and blue along the normal, each mapped from [0, 1] to [-1, 1]
+    NormalMap(Arc<dyn Texture>),
+    /// Height map whose first channel, times `scale`, displaces the surface
+    /// along its normal
+    Bump {
+        height: Arc<dyn Texture>,
+        scale: f64,
+    },
+}
+
+/// Shades a base material with a normal tilted by a normal or bump map.
+///
+/// Only shading sees the tilted normal. Which side of the surface a ray is
+/// on, and so refraction and media, still follow the geometric normal.
+pub struct NormalMapped {
+    pub base: Arc<dyn Material>,
+    pub perturbation: Arc<Perturbation>,
+    /// Tilted copies of the materials a mixed base picks from
+    variants: Variants,
+}
+
+impl NormalMapped {
+    pub fn normal_map(base: Arc<dyn Material>, normals: Arc<dyn Texture>) -> Self {
+        Self {
+            base,
+            perturbation: Arc::new(Perturbation::NormalMap(normals)),
+            variants: Variants::default(),
+        }
+    }
+
+    pub fn bump(base: Arc<dyn Material>, height: Arc<dyn Texture>, scale: f64) -> Self {
+        Self {
+            base,
+            perturbation: Arc::new(Perturbation::Bump { height, scale }),
+            variants: Variants::default(),
+        }
+    }
+
+    /// Same tilt over another base
+    fn around(&self, base: Arc<dyn Material>) -> Arc<dyn Material> {
+        Arc::new(Self {
+            base,
+            perturbation: self.perturbation.clone(),
+            variants: Variants::default(),
+        })
+    }
+
+    /// Copy of `hr` with the tilted normal. Normals tilted away from the
+    /// incoming ray would shade black and are left untouched.
+    fn perturb(&self, ray: &Ray, hr: &HitRecord) -> HitRecord {
+        let outward = if hr.front_face { hr.normal } else { -hr.normal };
+        let tilted = match self.perturbation.as_ref() {
+            Perturbation::NormalMap(normals) => {
+                let local = normals.value_at(hr) * 2.0 - DVec3::ONE;
+                let tangent = (hr.dpdu - outward * outward.dot(hr.dpdu)).normalize();
+                let mut bitangent = outward.cross(tangent);
+                if bitangent.dot(hr.dpdv) < 0.0 {
+                    bitangent = -bitangent;
+                }
+                local.x * tangent + local.y * bitangent + local.z * outward
+            }
+            Perturbation::Bump { height, scale } => {
+                let displacement =
+                    |u: f64, v: f64, point: DVec3| scale * height.value(u, v, point).x;
+                let d = displacement(hr.u, hr.v, hr.point);
+                let du = displacement(hr.u + BUMP_DELTA, hr.v, hr.point + BUMP_DELTA * hr.dpdu);
+                let dv = displacement(hr.u, hr.v + BUMP_DELTA, hr.point + BUMP_DELTA * hr.dpdv);
+
+                let dpdu = hr.dpdu + (du - d) / BUMP_DELTA * outward;
+                let dpdv = hr.dpdv + (dv - d) / BUMP_DELTA * outward;
+                let normal = dpdu.cross(dpdv);
+                if normal.dot(outward) < 0.0 {
+                    -normal
+                } else {
+                    normal
+                }
+            }
+        };
+
+        let mut perturbed = hr.clone();
+        let tilted = tilted.normalize();
+        let normal = if hr.front_face { tilted } else { -tilted };
+        if normal.is_finite() && normal.dot(ray.direction) < 0.0 {
+            perturbed.normal = normal;
+        }
+        perturbed
+    }
+}
+
+impl Material for NormalMapped {
+    /// Tilts the normal of the material picked by a mixed base
+    fn select(&self, hr: &HitRecord) -> Option<Arc<dyn Material>> {
+        self.variants
+            .select(self.base.as_ref(), hr, |base| self.around(base))
+    }
+
+    fn choices(&self) -> Vec<Arc<dyn Material>> {
+        self.variants
+            .choices(self.base.as_ref(), |base| self.around(base))
+    }
+
+    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
+        self.base.scatter(ray, &self.perturb(ray, hr))
+    }
+
+    fn eval(&self, ray: &Ray, hr: &HitRecord, direction: DVec3) -> DVec3 {
+        self.base.eval(ray, &self.perturb(ray, hr), direction)
+    }
+
+    fn emitted(&self, hr: &HitRecord) -> DVec3 {
+        self.base.emitted(hr)
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        self.base.medium()
+    }
+}
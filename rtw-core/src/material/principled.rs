@@ -0,0 +1,353 @@
+use glam::DVec3;
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hittable::HitRecord,
+    material::{
+        Material, Scatter,
+        microfacet::{Ggx, GgxReflectionPdf},
+    },
+    pdf::{CosinePdf, MixturePdf},
+    ray::Ray,
+    texture::{Texture, solid::SolidColor},
+    utils::{
+        onb::Onb,
+        vec::{reflect, refract},
+    },
+};
+
+/// Fixed roughness of the clearcoat layer, a thin glossy varnish
+const CLEARCOAT_ROUGHNESS: f64 = 0.2;
+
+/// Disney style "principled" material, mixing a diffuse base, a metallic or
+/// dielectric specular lobe, sheen, a clearcoat and glass like transmission.
+///
+/// Every parameter is a texture. Scalar parameters read the first channel
+/// of their texture and are expected in [0, 1], except `ior`.
+pub struct Principled {
+    pub base_color: Arc<dyn Texture>,
+    pub metallic: Arc<dyn Texture>,
+    pub roughness: Arc<dyn Texture>,
+    /// Strength of the non metallic highlight, 0.5 matches most dielectrics
+    pub specular: Arc<dyn Texture>,
+    /// Tints the non metallic highlight towards the base color
+    pub specular_tint: Arc<dyn Texture>,
+    /// Soft rim highlight for cloth
+    pub sheen: Arc<dyn Texture>,
+    pub clearcoat: Arc<dyn Texture>,
+    /// Fraction of light passing through the surface like smooth glass
+    pub transmission: Arc<dyn Texture>,
+    /// Index of refraction used for transmission
+    pub ior: Arc<dyn Texture>,
+}
+
+/// Parameters sampled at one surface point
+struct Lobes {
+    base_color: DVec3,
+    metallic: f64,
+    roughness: f64,
+    specular: f64,
+    specular_tint: f64,
+    sheen: f64,
+    clearcoat: f64,
+    transmission: f64,
+    ior: f64,
+}
+
+fn constant(value: f64) -> Arc<dyn Texture> {
+    Arc::new(SolidColor::new(DVec3::splat(value)))
+}
+
+fn schlick(f0: DVec3, cosine: f64) -> DVec3 {
+    f0 + (DVec3::ONE - f0) * (1.0 - cosine).clamp(0.0, 1.0).powi(5)
+}
+
+fn luminance(color: DVec3) -> f64 {
+    color.dot(DVec3::new(0.2126, 0.7152, 0.0722))
+}
+
+impl Principled {
+    /// Rough plastic of the given base color
+    pub fn from_color(base_color: DVec3) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(base_color)))
+    }
+
+    pub fn from_texture(base_color: Arc<dyn Texture>) -> Self {
+        Self {
+            base_color,
+            metallic: constant(0.0),
+            roughness: constant(0.5),
+            specular: constant(0.5),
+            specular_tint: constant(0.0),
+            sheen: constant(0.0),
+            clearcoat: constant(0.0),
+            transmission: constant(0.0),
+            ior: constant(1.5),
+        }
+    }
+
+    pub fn metallic(mut self, metallic: Arc<dyn Texture>) -> Self {
+        self.metallic = metallic;
+        self
+    }
+
+    pub fn roughness(mut self, roughness: Arc<dyn Texture>) -> Self {
+        self.roughness = roughness;
+        self
+    }
+
+    pub fn specular(mut self, specular: Arc<dyn Texture>) -> Self {
+        self.specular = specular;
+        self
+    }
+
+    pub fn specular_tint(mut self, specular_tint: Arc<dyn Texture>) -> Self {
+        self.specular_tint = specular_tint;
+        self
+    }
+
+    pub fn sheen(mut self, sheen: Arc<dyn Texture>) -> Self {
+        self.sheen = sheen;
+        self
+    }
+
+    pub fn clearcoat(mut self, clearcoat: Arc<dyn Texture>) -> Self {
+        self.clearcoat = clearcoat;
+        self
+    }
+
+    pub fn transmission(mut self, transmission: Arc<dyn Texture>) -> Self {
+        self.transmission = transmission;
+        self
+    }
+
+    pub fn ior(mut self, ior: Arc<dyn Texture>) -> Self {
+        self.ior = ior;
+        self
+    }
+
+    fn lobes(&self, hr: &HitRecord) -> Lobes {
+        let scalar = |texture: &Arc<dyn Texture>| texture.value_at(hr).x;
+        Lobes {
+            base_color: self.base_color.value_at(hr),
+            metallic: scalar(&self.metallic).clamp(0.0, 1.0),
+            roughness: scalar(&self.roughness).clamp(0.0, 1.0),
+            specular: scalar(&self.specular).max(0.0),
+            specular_tint: scalar(&self.specular_tint).clamp(0.0, 1.0),
+            sheen: scalar(&self.sheen).max(0.0),
+            clearcoat: scalar(&self.clearcoat).max(0.0),
+            transmission: scalar(&self.transmission).clamp(0.0, 1.0),
+            ior: scalar(&self.ior).max(1.0),
+        }
+    }
+}
+
+impl Lobes {
+    /// Hue of the base color without its brightness
+    fn tint(&self) -> DVec3 {
+        let lum = luminance(self.base_color);
+        if lum > 0.0 {
+            self.base_color / lum
+        } else {
+            DVec3::ONE
+        }
+    }
+
+    /// Reflectance of the specular lobe at normal incidence
+    fn specular_f0(&self) -> DVec3 {
+        let dielectric = 0.08 * self.specular * DVec3::ONE.lerp(self.tint(), self.specular_tint);
+        dielectric.lerp(self.base_color, self.metallic)
+    }
+
+    fn diffuse_weight(&self) -> f64 {
+        1.0 - self.metallic
+    }
+
+    fn specular(&self) -> Ggx {
+        Ggx::from_roughness(self.roughness, 0.0)
+    }
+
+    fn clearcoat(&self) -> Ggx {
+        Ggx::from_roughness(CLEARCOAT_ROUGHNESS, 0.0)
+    }
+
+    /// Smooth glass reflection or refraction. Light is tinted by the base
+    /// color once, on its way in.
+    fn transmit(&self, ray: &Ray, hr: &HitRecord) -> Scatter {
+        let ri = if hr.front_face {
+            1.0 / self.ior
+        } else {
+            self.ior
+        };
+
+        let unit_direction = ray.direction.normalize();
+        let cos_theta = f64::min(1.0, hr.normal.dot(-unit_direction));
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let r0 = ((1.0 - ri) / (1.0 + ri)).powi(2);
+        let reflectance = r0 + (1.0 - r0) * (1.0 - cos_theta).powi(5);
+
+        let (direction, attenuation) =
+            if ri * sin_theta > 1.0 || reflectance > rand::random::<f64>() {
+                (reflect(unit_direction, hr.normal), DVec3::ONE)
+            } else {
+                let tint = if hr.front_face {
+                    self.base_color
+                } else {
+                    DVec3::ONE
+                };
+                (refract(unit_direction, hr.normal, ri), tint)
+            };
+
+        Scatter::Specular {
+            scattered: Box::new(ray.scattered(hr.point, direction)),
+            attenuation,
+        }
+    }
+}
+
+impl Material for Principled {
+    /// Transmission is picked with a chance equal to its weight, so the
+    /// opaque lobes sampled otherwise need no extra weighting in `eval`.
+    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
+        let lobes = self.lobes(hr);
+        if lobes.transmission > rand::random::<f64>() {
+            return Some(lobes.transmit(ray, hr));
+        }
+
+        let wo = -ray.direction.normalize();
+        let uvw = Onb::from_tangent(hr.normal, hr.dpdu);
+        let specular_weight = luminance(schlick(lobes.specular_f0(), hr.normal.dot(wo)));
+        Some(Scatter::Pdf(Box::new(MixturePdf::new(vec![
+            (lobes.diffuse_weight(), Box::new(CosinePdf::new(hr.normal))),
+            (
+                specular_weight.max(0.1),
+                Box::new(GgxReflectionPdf::new(uvw, wo, lobes.specular())),
+            ),
+            (
+                0.25 * lobes.clearcoat,
+                Box::new(GgxReflectionPdf::new(uvw, wo, lobes.clearcoat())),
+            ),
+        ]))))
+    }
+
+    /// Opaque part of the material only, transmission is sampled
+    /// separately in `scatter`
+    fn eval(&self, ray: &Ray, hr: &HitRecord, direction: DVec3) -> DVec3 {
+        let uvw = Onb::from_tangent(hr.normal, hr.dpdu);
+        let wo = uvw.local(-ray.direction.normalize());
+        let wi = uvw.local(direction.normalize());
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return DVec3::ZERO;
+        }
+
+        let lobes = self.lobes(hr);
+        let h = (wo + wi).normalize();
+        let cos_d = wi.dot(h);
+
+        // Burley diffuse with retro reflection at grazing angles
+        let fd90 = 0.5 + 2.0 * lobes.roughness * cos_d * cos_d;
+        let fresnel_wo = 1.0 + (fd90 - 1.0) * (1.0 - wo.z).powi(5);
+        let fresnel_wi = 1.0 + (fd90 - 1.0) * (1.0 - wi.z).powi(5);
+        let diffuse = lobes.base_color / PI * fresnel_wo * fresnel_wi;
+        let sheen = DVec3::splat(lobes.sheen * (1.0 - cos_d).powi(5));
+        let base = lobes.diffuse_weight() * (diffuse + sheen) * wi.z;
+
+        let ggx = lobes.specular();
+        let specular =
+            schlick(lobes.specular_f0(), cos_d) * ggx.d(h) * ggx.g2(wo, wi) / (4.0 * wo.z);
+
+        let coat = lobes.clearcoat();
+        let clearcoat = 0.25
+            * lobes.clearcoat
+            * schlick(DVec3::splat(0.04), cos_d)
+            * coat.d(h)
+            * coat.g2(wo, wi)
+            / (4.0 * wo.z);
+
+        base + specular + clearcoat
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(ray: &Ray, outward_normal: DVec3, material: Principled) -> HitRecord {
+        HitRecord::init(
+            DVec3::ZERO,
+            outward_normal,
+            1.0,
+            (0.5, 0.5),
+            ray,
+            Arc::new(material),
+        )
+    }
+
+    /// Reflected fraction of light arriving along `wo`, with the midpoint
+    /// rule over the hemisphere
+    fn albedo(material: Principled, wo: DVec3) -> DVec3 {
+        let ray = Ray::new(wo, -wo);
+        let hr = hit(&ray, DVec3::Z, material);
+        let (n_theta, n_phi) = (200, 400);
+        let (d_theta, d_phi) = (0.5 * PI / n_theta as f64, 2.0 * PI / n_phi as f64);
+        let mut total = DVec3::ZERO;
+        for i in 0..n_theta {
+            let theta = (i as f64 + 0.5) * d_theta;
+            for j in 0..n_phi {
+                let phi = (j as f64 + 0.5) * d_phi;
+                let direction = DVec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
+                let f_cos = hr.material.eval(&ray, &hr, direction);
+                total += f_cos * theta.sin() * d_theta * d_phi;
+            }
+        }
+        total
+    }
+
+    #[test]
+    fn opaque_lobes_reflect_about_the_incoming_light() {
+        let wo = DVec3::new(0.5, 0.0, 0.75f64.sqrt());
+        // Like Disney's model, the diffuse base isn't dimmed by the highlight
+        // above it, so white plastic reflects a few percent too much
+        for (metallic, limit) in [(0.0, 1.06), (1.0, 1.0)] {
+            for roughness in [0.2, 0.8] {
+                let material = Principled::from_color(DVec3::ONE)
+                    .metallic(constant(metallic))
+                    .roughness(constant(roughness));
+                let albedo = albedo(material, wo).x;
+                assert!(
+                    albedo > 0.5 && albedo < limit,
+                    "metallic {metallic}, roughness {roughness}: {albedo}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn transmission_is_tinted_on_the_way_in_only() {
+        let color = DVec3::new(0.8, 0.4, 0.2);
+        let material = || Principled::from_color(color).transmission(constant(1.0));
+        let ray = Ray::new(DVec3::Z, -DVec3::Z);
+
+        // Entering through the front face, then leaving through the back
+        let entering = hit(&ray, DVec3::Z, material());
+        let leaving = hit(&ray, -DVec3::Z, material());
+        let attenuations = |hr: &HitRecord| -> Vec<DVec3> {
+            (0..200)
+                .map(|_| match hr.material.scatter(&ray, hr) {
+                    Some(Scatter::Specular { attenuation, .. }) => attenuation,
+                    _ => panic!("transmission scatters specularly"),
+                })
+                .collect()
+        };
+        assert!(attenuations(&entering).contains(&color));
+        assert!(
+            attenuations(&leaving)
+                .iter()
+                .all(|attenuation| *attenuation == DVec3::ONE)
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use glam::DVec3;
+use rand::Rng;
+
+use crate::{
+    hittable::HitRecord,
+    material::{
+        Material, Scatter,
+        microfacet::{Ggx, fresnel_dielectric},
+    },
+    medium::{Medium, medium_id},
+    pdf::Pdf,
+    ray::Ray,
+    utils::{
+        onb::Onb,
+        vec::{reflect, refract},
+    },
+};
+
+/// Below this roughness the surface is treated as smooth glass
+const SMOOTH_ROUGHNESS: f64 = 1e-3;
+
+/// Glass with a GGX microfacet surface, such as frosted or ground glass.
+///
+/// Light is reflected or refracted through each microfacet in proportion to
+/// the exact dielectric Fresnel term, following Walter et al. 2007
+/// "Microfacet Models for Refraction through Rough Surfaces".
+pub struct RoughDielectric {
+    pub refraction_index: f64,
+    pub roughness: f64,
+}
+
+impl RoughDielectric {
+    pub fn new(refraction_index: f64, roughness: f64) -> Self {
+        Self {
+            refraction_index,
+            roughness,
+        }
+    }
+
+    /// Index on the far side of the surface over the index on the side of
+    /// the incoming ray
+    fn eta(&self, ray: &Ray, hr: &HitRecord) -> f64 {
+        let inside = self.medium_inside();
+        let (near, far) = ray
+            .media
+            .sides(medium_id(&hr.material), &inside, hr.front_face);
+        let index = |medium: Option<&Medium>| medium.map_or(1.0, |medium| medium.refraction_index);
+        index(far) / index(near)
+    }
+
+    fn medium_inside(&self) -> Medium {
+        Medium {
+            refraction_index: self.refraction_index,
+            dispersion: None,
+            absorption: DVec3::ZERO,
+            scattering: DVec3::ZERO,
+            density: None,
+            anisotropy: 0.0,
+            priority: 0,
+            index_matched: false,
+        }
+    }
+}
+
+/// `uvw` turned over when `wo` is below its surface, which shading normals
+/// can cause. Visible normal sampling only handles directions above the
+/// surface, and turning about `u` leaves the distribution unchanged.
+fn facing(uvw: Onb, wo: DVec3) -> Onb {
+    if wo.dot(uvw.w) < 0.0 {
+        Onb {
+            u: uvw.u,
+            v: -uvw.v,
+            w: -uvw.w,
+        }
+    } else {
+        uvw
+    }
+}
+
+/// Whether `wi` leaves on the same side of the surface as `wo`
+fn is_reflection(wo: DVec3, wi: DVec3) -> bool {
+    wo.z * wi.z > 0.0
+}
+
+/// Microfacet normal turning `wo` into `wi`, by reflection when both are on
+/// the same side of the surface and by refraction otherwise. None when no
+/// microfacet facing `wo` can do it.
+fn half_vector(wo: DVec3, wi: DVec3, eta: f64) -> Option<DVec3> {
+    let reflected = is_reflection(wo, wi);
+    let h = if reflected { wo + wi } else { wo + eta * wi };
+    if h.length_squared() == 0.0 {
+        return None;
+    }
+    let h = h.normalize() * h.z.signum();
+
+    // Refracted light has to cross the microfacet
+    let valid = if reflected {
+        wo.dot(h) > 0.0 && wi.dot(h) > 0.0
+    } else {
+        wo.dot(h) > 0.0 && wi.dot(h) < 0.0
+    };
+    valid.then_some(h)
+}
+
+/// Reflection and refraction directions through visible GGX normals, picked
+/// according to the Fresnel term of the sampled microfacet
+pub struct RoughDielectricPdf {
+    uvw: Onb,
+    /// Direction towards the viewer in the local frame
+    wo: DVec3,
+    ggx: Ggx,
+    eta: f64,
+}
+
+impl RoughDielectricPdf {
+    /// Scattering about the `w` axis of `uvw`, or its opposite when `wo` is
+    /// below it
+    pub fn new(uvw: Onb, wo: DVec3, ggx: Ggx, eta: f64) -> Self {
+        let uvw = facing(uvw, wo);
+        Self {
+            uvw,
+            wo: uvw.local(wo),
+            ggx,
+            eta,
+        }
+    }
+}
+
+impl Pdf for RoughDielectricPdf {
+    fn value(&self, direction: DVec3) -> f64 {
+        let wi = self.uvw.local(direction.normalize());
+        let Some(h) = half_vector(self.wo, wi, self.eta) else {
+            return 0.0;
+        };
+
+        let fresnel = fresnel_dielectric(self.wo.dot(h), self.eta);
+        let normal_pdf = self.ggx.visible_normal_pdf(self.wo, h);
+        if is_reflection(self.wo, wi) {
+            fresnel * normal_pdf / (4.0 * self.wo.dot(h))
+        } else {
+            // Change of variables from the microfacet normal to the
+            // refracted direction
+            let denom = self.wo.dot(h) + self.eta * wi.dot(h);
+            let jacobian = self.eta * self.eta * wi.dot(h).abs() / (denom * denom);
+            (1.0 - fresnel) * normal_pdf * jacobian
+        }
+    }
+
+    fn generate(&self) -> DVec3 {
+        let h = self.ggx.sample_visible_normal(self.wo);
+        let fresnel = fresnel_dielectric(self.wo.dot(h), self.eta);
+        let wi = if rand::rng().random::<f64>() < fresnel {
+            reflect(-self.wo, h)
+        } else {
+            refract(-self.wo, h, 1.0 / self.eta)
+        };
+        self.uvw.transform(wi)
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
+        let wo = -ray.direction.normalize();
+        let eta = self.eta(ray, hr);
+
+        if self.roughness < SMOOTH_ROUGHNESS {
+            let fresnel = fresnel_dielectric(hr.normal.dot(wo), eta);
+            let direction = if rand::rng().random::<f64>() < fresnel {
+                reflect(-wo, hr.normal)
+            } else {
+                refract(-wo, hr.normal, 1.0 / eta)
+            };
+            return Some(Scatter::Specular {
+                scattered: Box::new(ray.scattered(hr.point, direction)),
+                attenuation: DVec3::ONE,
+            });
+        }
+
+        Some(Scatter::Pdf(Box::new(RoughDielectricPdf::new(
+            Onb::from_tangent(hr.normal, hr.dpdu),
+            wo,
+            Ggx::from_roughness(self.roughness, 0.0),
+            eta,
+        ))))
+    }
+
+    fn eval(&self, ray: &Ray, hr: &HitRecord, direction: DVec3) -> DVec3 {
+        let uvw = facing(Onb::from_tangent(hr.normal, hr.dpdu), -ray.direction);
+        let wo = uvw.local(-ray.direction.normalize());
+        let wi = uvw.local(direction.normalize());
+        let eta = self.eta(ray, hr);
+        let Some(h) = half_vector(wo, wi, eta) else {
+            return DVec3::ZERO;
+        };
+
+        let ggx = Ggx::from_roughness(self.roughness, 0.0);
+        let fresnel = fresnel_dielectric(wo.dot(h), eta);
+        let dg = ggx.d(h) * ggx.g2(wo, wi);
+
+        let f_cos = if is_reflection(wo, wi) {
+            fresnel * dg / (4.0 * wo.z.abs())
+        } else {
+            // Radiance is compressed by eta^2 when entering a denser medium,
+            // which cancels the eta^2 of the change of variables
+            let denom = wo.dot(h) + eta * wi.dot(h);
+            (1.0 - fresnel) * dg * (wi.dot(h) * wo.dot(h)).abs() / (wo.z.abs() * denom * denom)
+        };
+        DVec3::splat(f_cos)
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        Some(self.medium_inside())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    /// Integral of the density over the sphere, with the midpoint rule over
+    /// theta and phi
+    fn integral(pdf: &RoughDielectricPdf) -> f64 {
+        let (n_theta, n_phi) = (400, 800);
+        let (d_theta, d_phi) = (PI / n_theta as f64, 2.0 * PI / n_phi as f64);
+        let mut total = 0.0;
+        for i in 0..n_theta {
+            let theta = (i as f64 + 0.5) * d_theta;
+            for j in 0..n_phi {
+                let phi = (j as f64 + 0.5) * d_phi;
+                let direction = DVec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
+                total += pdf.value(direction) * theta.sin() * d_theta * d_phi;
+            }
+        }
+        total
+    }
+
+    fn pdf(wo: DVec3, eta: f64) -> RoughDielectricPdf {
+        let ggx = Ggx::from_roughness(0.6, 0.0);
+        RoughDielectricPdf::new(Onb::new(DVec3::Z), wo.normalize(), ggx, eta)
+    }
+
+    #[test]
+    fn density_integrates_to_at_most_one() {
+        for eta in [1.5, 1.0 / 1.5] {
+            let total = integral(&pdf(DVec3::new(0.6, 0.0, 0.8), eta));
+            assert!(total > 0.5 && total < 1.01, "eta {eta}: {total}");
+        }
+    }
+
+    #[test]
+    fn samples_below_the_shading_normal_have_density() {
+        let pdf = pdf(DVec3::new(0.6, 0.2, -0.3), 1.5);
+        // Only reflections off microfacets that send light back through the
+        // surface are lost
+        let with_density = (0..1000)
+            .filter(|_| pdf.value(pdf.generate()) > 0.0)
+            .count();
+        assert!(with_density > 900, "{with_density}");
+        let total = integral(&pdf);
+        assert!(total > 0.5 && total < 1.01, "{total}");
+    }
+}
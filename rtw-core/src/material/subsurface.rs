@@ -0,0 +1,57 @@
+use glam::DVec3;
+
+use crate::{
+    hittable::HitRecord,
+    material::{Material, Scatter, dielectric::Dielectric},
+    medium::Medium,
+    ray::Ray,
+};
+
+/// Translucent material such as skin, wax, marble or milk.
+///
+/// Light refracts through a smooth dielectric surface and then random walks
+/// through the inside of the object, scattering and being absorbed until it
+/// finds its way out again. Objects using it have to be closed.
+pub struct Subsurface {
+    boundary: Dielectric,
+}
+
+impl Subsurface {
+    /// Scattering and absorption coefficients per unit distance, per color
+    /// channel
+    pub fn from_coefficients(scattering: DVec3, absorption: DVec3, refraction_index: f64) -> Self {
+        Self {
+            boundary: Dielectric::new(refraction_index)
+                .scattering(scattering)
+                .absorption(absorption),
+        }
+    }
+
+    /// Material that looks like `albedo` once light has bounced around
+    /// inside, with `mean_free_path` the average distance travelled between
+    /// two scattering events per color channel. Longer paths let light
+    /// bleed further through the object.
+    ///
+    /// The single scattering albedo of the medium is found from the visible
+    /// one with the fit from Chiang et al. 2016 "Practical and Controllable
+    /// Subsurface Scattering for Production Path Tracing".
+    pub fn from_albedo(albedo: DVec3, mean_free_path: DVec3, refraction_index: f64) -> Self {
+        let a = albedo.clamp(DVec3::ZERO, DVec3::splat(0.999));
+        let root = 4.09712 + 4.20863 * a - (9.59217 + 41.6808 * a + 17.7126 * a * a).map(f64::sqrt);
+        let single_scattering = DVec3::ONE - root * root;
+
+        let extinction = DVec3::ONE / mean_free_path.max(DVec3::splat(1e-6));
+        let scattering = single_scattering * extinction;
+        Self::from_coefficients(scattering, extinction - scattering, refraction_index)
+    }
+}
+
+impl Material for Subsurface {
+    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> Option<Scatter> {
+        self.boundary.scatter(ray, hr)
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        self.boundary.medium()
+    }
+}
@@ -0,0 +1,64 @@
+use glam::DVec3;
+use std::sync::Arc;
+
+use crate::{
+    hittable::HitRecord,
+    material::{Material, Scatter},
+    medium::{Medium, density::Density},
+    ray::Ray,
+};
+
+/// Participating medium without a visible surface, such as fog, smoke or
+/// clouds. Closed objects made of it only mark where the medium is, light
+/// crosses their surface without bending.
+pub struct Volume {
+    medium: Medium,
+}
+
+impl Volume {
+    /// Uniform medium with scattering and absorption coefficients per unit
+    /// distance, per color channel
+    pub fn new(scattering: DVec3, absorption: DVec3) -> Self {
+        Self {
+            medium: Medium {
+                refraction_index: 1.0,
+                dispersion: None,
+                absorption,
+                scattering,
+                density: None,
+                anisotropy: 0.0,
+                priority: 0,
+                index_matched: true,
+            },
+        }
+    }
+
+    /// Scales the coefficients by a density varying through the volume
+    pub fn density(mut self, density: Arc<dyn Density>) -> Self {
+        self.medium.density = Some(density);
+        self
+    }
+
+    /// Henyey-Greenstein asymmetry, positive values scatter light mostly
+    /// forwards as clouds and haze do
+    pub fn anisotropy(mut self, anisotropy: f64) -> Self {
+        self.medium.anisotropy = anisotropy;
+        self
+    }
+
+    pub fn priority(mut self, priority: u32) -> Self {
+        self.medium.priority = priority;
+        self
+    }
+}
+
+impl Material for Volume {
+    /// Never called, rays always pass straight through the surface
+    fn scatter(&self, _ray: &Ray, _hr: &HitRecord) -> Option<Scatter> {
+        None
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        Some(self.medium.clone())
+    }
+}
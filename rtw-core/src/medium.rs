@@ -0,0 +1,511 @@
+pub mod density;
+
+use glam::DVec3;
+use rand::Rng;
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    material::{Material, dielectric::Dispersion},
+    medium::density::Density,
+    pdf::Pdf,
+    ray::Ray,
+    utils::onb::Onb,
+};
+
+/// Deepest nesting of media a ray keeps track of, further media are ignored
+const MAX_NESTING: usize = 4;
+
+/// Inside of a closed dielectric object
+#[derive(Clone)]
+pub struct Medium {
+    pub refraction_index: f64,
+    pub dispersion: Option<Dispersion>,
+    /// Fraction of light absorbed per unit distance, per color channel
+    pub absorption: DVec3,
+    /// Fraction of light scattered off course per unit distance, per color
+    /// channel
+    pub scattering: DVec3,
+    /// Varying density scaling both coefficients, None for a uniform medium
+    pub density: Option<Arc<dyn Density>>,
+    /// Henyey-Greenstein asymmetry of scattering, from -1 for scattering
+    /// straight back through 0 for all directions alike to 1 for straight on
+    pub anisotropy: f64,
+    /// Where media overlap, the one with the highest priority fills the
+    /// overlap and the surfaces of the others are ignored there
+    pub priority: u32,
+    /// Takes the refraction index of the medium around it, so its surface
+    /// never bends or reflects light, as for fog and smoke
+    pub index_matched: bool,
+}
+
+impl Medium {
+    /// Refraction index at the hero wavelength in spectral mode
+    pub fn index(&self, wavelength: Option<f64>) -> f64 {
+        match (self.dispersion, wavelength) {
+            (Some(dispersion), Some(wavelength)) => dispersion.index(wavelength),
+            _ => self.refraction_index,
+        }
+    }
+
+    /// Samples where `ray` scatters within `distance` of its origin, if it
+    /// does, given the ray is inside this medium
+    pub fn free_flight(&self, ray: &Ray, distance: f64) -> Flight {
+        let (absorption, scattering) = self.coefficients(ray);
+        match &self.density {
+            None => homogeneous_flight(absorption, scattering, distance),
+            Some(density) => {
+                tracked_flight(absorption, scattering, density.as_ref(), ray, distance)
+            }
+        }
+    }
+
+    /// Fraction of light left after travelling `distance` along `ray`
+    /// through this medium.
+    ///
+    /// Varying media use plain ratio tracking, an unbiased estimate
+    /// introduced alongside its residual variant in Novák et al. 2014
+    /// "Residual Ratio Tracking for Estimating Attenuation in Participating
+    /// Media". The residual variant isn't used.
+    pub fn transmittance(&self, ray: &Ray, distance: f64) -> DVec3 {
+        let (absorption, scattering) = self.coefficients(ray);
+        let extinction = absorption + scattering;
+        let Some(density) = &self.density else {
+            return transmittance(extinction, distance);
+        };
+        let majorant = extinction.max_element() * density.max_density();
+        if majorant <= 0.0 || !distance.is_finite() {
+            return DVec3::ONE;
+        }
+
+        let direction = ray.direction.normalize();
+        let mut rng = rand::rng();
+        let mut transmittance = DVec3::ONE;
+        let mut t = 0.0;
+        loop {
+            t -= (1.0 - rng.random::<f64>()).ln() / majorant;
+            if t >= distance || transmittance == DVec3::ZERO {
+                return transmittance;
+            }
+            let density = density.density(ray.origin + direction * t);
+            transmittance *= DVec3::ONE - extinction * density / majorant;
+        }
+    }
+
+    /// Coefficients at the wavelengths of `ray`
+    fn coefficients(&self, ray: &Ray) -> (DVec3, DVec3) {
+        match ray.wavelengths {
+            Some(wavelengths) => (
+                wavelengths.upsample(self.absorption),
+                wavelengths.upsample(self.scattering),
+            ),
+            None => (self.absorption, self.scattering),
+        }
+    }
+}
+
+/// Identifies the medium of a material, objects sharing a material share
+/// the medium
+pub fn medium_id(material: &Arc<dyn Material>) -> usize {
+    Arc::as_ptr(material) as *const () as usize
+}
+
+/// Media a ray is currently inside, following Schmidt and Budge 2002
+/// "Simple Nested Dielectrics in Ray Traced Images".
+///
+/// Outside of every medium the ray travels through vacuum.
+#[derive(Clone, Default)]
+pub struct MediumStack {
+    entries: [Option<(usize, Medium)>; MAX_NESTING],
+}
+
+impl MediumStack {
+    /// Medium filling the space around the ray, the highest priority one
+    /// and the latest entered among equal priorities
+    pub fn current(&self) -> Option<&Medium> {
+        self.top(|_, _| true).map(|(_, medium)| medium)
+    }
+
+    /// Media setting the refraction index on the near and far side of a
+    /// real boundary of `medium`, None standing for vacuum. Index matched
+    /// media are looked through to the medium they sit in.
+    pub fn sides<'a>(
+        &'a self,
+        id: usize,
+        medium: &'a Medium,
+        entering: bool,
+    ) -> (Option<&'a Medium>, Option<&'a Medium>) {
+        let around = |skip| {
+            self.top(|entry_id, medium| Some(entry_id) != skip && !medium.index_matched)
+                .map(|(_, medium)| medium)
+        };
+        if entering {
+            (around(None), Some(medium))
+        } else {
+            (Some(medium), around(Some(id)))
+        }
+    }
+
+    /// Highest priority entry among those `keep` accepts, the latest entered
+    /// among equal priorities
+    fn top(&self, keep: impl Fn(usize, &Medium) -> bool) -> Option<&(usize, Medium)> {
+        self.entries
+            .iter()
+            .flatten()
+            .filter(|(id, medium)| keep(*id, medium))
+            .fold(None, |best: Option<&(usize, Medium)>, entry| match best {
+                Some(best) if best.1.priority > entry.1.priority => Some(best),
+                _ => Some(entry),
+            })
+    }
+
+    /// Whether a surface of `medium` is a real boundary. Entering a medium
+    /// of lower priority than the current one, or leaving one that doesn't
+    /// fill the space, changes nothing and the ray should pass straight
+    /// through. Overlapping objects of the same material merge into one
+    /// medium, so their inner surfaces are skipped too.
+    pub fn is_boundary(&self, id: usize, medium: &Medium, entering: bool) -> bool {
+        let inside = self
+            .entries
+            .iter()
+            .flatten()
+            .filter(|(entry_id, _)| *entry_id == id)
+            .count();
+        let current = self.top(|_, _| true);
+        if entering {
+            inside == 0 && current.is_none_or(|(_, current)| medium.priority >= current.priority)
+        } else {
+            match inside {
+                0 => true,
+                1 => current.is_some_and(|(current_id, _)| *current_id == id),
+                _ => false,
+            }
+        }
+    }
+
+    /// Whether light crosses a surface of `medium` without bending or
+    /// reflecting, either because it isn't a real boundary or because both
+    /// sides have the same refraction index, like the edge of a cloud.
+    /// `wavelength` is the hero wavelength in spectral mode.
+    pub fn is_invisible(
+        &self,
+        id: usize,
+        medium: &Medium,
+        entering: bool,
+        wavelength: Option<f64>,
+    ) -> bool {
+        if medium.index_matched || !self.is_boundary(id, medium, entering) {
+            return true;
+        }
+        let (near, far) = self.sides(id, medium, entering);
+        let index = |medium: Option<&Medium>| medium.map_or(1.0, |medium| medium.index(wavelength));
+        index(near) == index(far)
+    }
+
+    /// Records crossing a surface of `medium` in or out
+    pub fn cross(&mut self, id: usize, medium: Medium, entering: bool) {
+        if entering {
+            if let Some(slot) = self.entries.iter_mut().find(|entry| entry.is_none()) {
+                *slot = Some((id, medium));
+            }
+        } else if let Some(index) = self
+            .entries
+            .iter()
+            .rposition(|entry| entry.as_ref().is_some_and(|(entry_id, _)| *entry_id == id))
+        {
+            // Keep entries in the order they were entered
+            self.entries[index..].rotate_left(1);
+            self.entries[MAX_NESTING - 1] = None;
+        }
+    }
+}
+
+/// Outcome of a ray travelling through a medium
+pub enum Flight {
+    /// The ray scattered after `distance`, with `weight` the ratio of
+    /// scattering coefficient times transmittance over sampling density
+    Scattered { distance: f64, weight: DVec3 },
+    /// The ray made it through, `weight` being the transmittance over the
+    /// chance of getting this far
+    Passed { weight: DVec3 },
+    /// The ray was absorbed on the way
+    Absorbed,
+}
+
+/// Flight through a uniform medium.
+///
+/// Coefficients differ per channel, so the distance is drawn for a random
+/// channel and weighted by the average density over all of them. Media
+/// that only absorb attenuate the ray deterministically.
+fn homogeneous_flight(absorption: DVec3, scattering: DVec3, distance: f64) -> Flight {
+    let extinction = absorption + scattering;
+    if scattering == DVec3::ZERO {
+        return Flight::Passed {
+            weight: transmittance(extinction, distance),
+        };
+    }
+
+    let mut rng = rand::rng();
+    let channel = extinction[rng.random_range(0..3)];
+    let sampled = -(1.0 - rng.random::<f64>()).ln() / channel;
+    if sampled < distance {
+        let transmittance = transmittance(extinction, sampled);
+        let pdf = (extinction * transmittance).element_sum() / 3.0;
+        Flight::Scattered {
+            distance: sampled,
+            weight: scattering * transmittance / pdf,
+        }
+    } else {
+        let transmittance = transmittance(extinction, distance);
+        let pdf = transmittance.element_sum() / 3.0;
+        Flight::Passed {
+            weight: transmittance / pdf,
+        }
+    }
+}
+
+/// Flight through a medium of varying density by delta tracking.
+///
+/// Steps are drawn as if the medium was uniformly as dense as it gets, and
+/// at each one the ray is absorbed, scattered or goes on through the made
+/// up "null" part of the density. With coefficients differing per channel
+/// the choice follows the spectral tracking of Kutz et al. 2017 "Spectral
+/// and Decomposition Tracking for Rendering Heterogeneous Volumes", which
+/// stays unbiased by carrying the mismatch in the weight.
+fn tracked_flight(
+    absorption: DVec3,
+    scattering: DVec3,
+    density: &dyn Density,
+    ray: &Ray,
+    distance: f64,
+) -> Flight {
+    let majorant = (absorption + scattering).max_element() * density.max_density();
+    // Rays leaking out of a closed volume shouldn't walk forever
+    if majorant <= 0.0 || !distance.is_finite() {
+        return Flight::Passed { weight: DVec3::ONE };
+    }
+
+    let direction = ray.direction.normalize();
+    let mut rng = rand::rng();
+    let mut weight = DVec3::ONE;
+    let mut t = 0.0;
+    loop {
+        t -= (1.0 - rng.random::<f64>()).ln() / majorant;
+        if t >= distance {
+            return Flight::Passed { weight };
+        }
+
+        let density = density.density(ray.origin + direction * t);
+        let absorbed = absorption * density;
+        let scattered = scattering * density;
+        let null = DVec3::splat(majorant) - absorbed - scattered;
+
+        let (p_absorbed, p_scattered, p_null) = (
+            (absorbed * weight).element_sum(),
+            (scattered * weight).element_sum(),
+            (null * weight).element_sum(),
+        );
+        let xi = rng.random::<f64>() * (p_absorbed + p_scattered + p_null);
+        if xi < p_absorbed {
+            return Flight::Absorbed;
+        } else if xi < p_absorbed + p_scattered {
+            return Flight::Scattered {
+                distance: t,
+                weight: weight * scattered * weight.element_sum() / p_scattered,
+            };
+        }
+        weight *= null * weight.element_sum() / p_null;
+    }
+}
+
+/// Fraction of light left after `distance` through a uniform medium,
+/// channels without extinction keep everything even over an infinite
+/// distance
+fn transmittance(extinction: DVec3, distance: f64) -> DVec3 {
+    DVec3::select(
+        extinction.cmpeq(DVec3::ZERO),
+        DVec3::ONE,
+        (-extinction * distance).exp(),
+    )
+}
+
+/// Henyey-Greenstein phase function, scattering light travelling along a
+/// direction with a preference for going forwards or backwards set by the
+/// asymmetry `g`. It's a normalized density, so sampling it is exact.
+pub struct HenyeyGreenstein {
+    uvw: Onb,
+    g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(direction: DVec3, g: f64) -> Self {
+        Self {
+            uvw: Onb::new(direction),
+            g: g.clamp(-0.99, 0.99),
+        }
+    }
+}
+
+impl Pdf for HenyeyGreenstein {
+    fn value(&self, direction: DVec3) -> f64 {
+        let cos_theta = direction.normalize().dot(self.uvw.w);
+        let g = self.g;
+        let denom = 1.0 + g * g - 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denom * denom.sqrt())
+    }
+
+    fn generate(&self) -> DVec3 {
+        let mut rng = rand::rng();
+        let (r1, r2) = (rng.random::<f64>(), rng.random::<f64>());
+        let g = self.g;
+        let cos_theta = if g.abs() < 1e-3 {
+            1.0 - 2.0 * r1
+        } else {
+            let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * r1);
+            ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+        };
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * r2;
+        self.uvw.transform(DVec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn medium(refraction_index: f64, priority: u32) -> Medium {
+        Medium {
+            refraction_index,
+            dispersion: None,
+            absorption: DVec3::ZERO,
+            scattering: DVec3::ZERO,
+            density: None,
+            anisotropy: 0.0,
+            priority,
+            index_matched: false,
+        }
+    }
+
+    fn index(medium: Option<&Medium>) -> Option<f64> {
+        medium.map(|medium| medium.refraction_index)
+    }
+
+    #[test]
+    fn lower_priority_medium_inside_higher_one_is_hidden_until_exposed() {
+        let (glass, water) = (medium(1.5, 2), medium(1.33, 1));
+        let mut stack = MediumStack::default();
+
+        assert!(stack.is_boundary(1, &glass, true));
+        stack.cross(1, glass.clone(), true);
+
+        // Water surface inside the glass is overlapped and skipped
+        assert!(!stack.is_boundary(2, &water, true));
+        stack.cross(2, water.clone(), true);
+        assert_eq!(index(stack.current()), Some(1.5));
+
+        // Leaving the glass into the water it held
+        assert!(stack.is_boundary(1, &glass, false));
+        let (near, far) = stack.sides(1, &glass, false);
+        assert_eq!((index(near), index(far)), (Some(1.5), Some(1.33)));
+        stack.cross(1, glass, false);
+        assert_eq!(index(stack.current()), Some(1.33));
+
+        assert!(stack.is_boundary(2, &water, false));
+        let (near, far) = stack.sides(2, &water, false);
+        assert_eq!((index(near), index(far)), (Some(1.33), None));
+        stack.cross(2, water, false);
+        assert!(stack.current().is_none());
+    }
+
+    #[test]
+    fn leaving_a_hidden_medium_is_not_a_boundary() {
+        let (glass, water) = (medium(1.5, 2), medium(1.33, 1));
+        let mut stack = MediumStack::default();
+        stack.cross(1, glass.clone(), true);
+        stack.cross(2, water.clone(), true);
+
+        assert!(!stack.is_boundary(2, &water, false));
+        stack.cross(2, water, false);
+        assert_eq!(index(stack.current()), Some(1.5));
+        assert!(stack.is_boundary(1, &glass, false));
+    }
+
+    #[test]
+    fn equal_priorities_nest_in_entry_order() {
+        let (glass, bubble) = (medium(1.5, 0), medium(1.0, 0));
+        let mut stack = MediumStack::default();
+        stack.cross(1, glass.clone(), true);
+
+        assert!(stack.is_boundary(2, &bubble, true));
+        let (near, far) = stack.sides(2, &bubble, true);
+        assert_eq!((index(near), index(far)), (Some(1.5), Some(1.0)));
+        stack.cross(2, bubble.clone(), true);
+        assert_eq!(index(stack.current()), Some(1.0));
+
+        assert!(stack.is_boundary(2, &bubble, false));
+        let (near, far) = stack.sides(2, &bubble, false);
+        assert_eq!((index(near), index(far)), (Some(1.0), Some(1.5)));
+        stack.cross(2, bubble, false);
+        assert_eq!(index(stack.current()), Some(1.5));
+    }
+
+    #[test]
+    fn overlapping_objects_of_one_material_merge() {
+        let glass = medium(1.5, 0);
+        let mut stack = MediumStack::default();
+
+        assert!(stack.is_boundary(1, &glass, true));
+        stack.cross(1, glass.clone(), true);
+        assert!(!stack.is_boundary(1, &glass, true));
+        stack.cross(1, glass.clone(), true);
+
+        assert!(!stack.is_boundary(1, &glass, false));
+        stack.cross(1, glass.clone(), false);
+        assert!(stack.is_boundary(1, &glass, false));
+        stack.cross(1, glass, false);
+        assert!(stack.current().is_none());
+    }
+
+    #[test]
+    fn leaving_a_medium_never_entered_is_a_boundary() {
+        let glass = medium(1.5, 0);
+        let mut stack = MediumStack::default();
+
+        assert!(stack.is_boundary(1, &glass, false));
+        stack.cross(1, glass, false);
+        assert!(stack.current().is_none());
+    }
+
+    #[test]
+    fn volume_inside_glass_keeps_the_glass_index() {
+        let (bottle, bead) = (medium(1.5, 0), medium(2.0, 0));
+        let smoke = Medium {
+            index_matched: true,
+            ..medium(1.0, 0)
+        };
+        let mut stack = MediumStack::default();
+        stack.cross(1, bottle.clone(), true);
+
+        // The smoke surface never bends light, even inside the bottle
+        assert!(stack.is_invisible(2, &smoke, true, None));
+        stack.cross(2, smoke.clone(), true);
+        assert_eq!(index(stack.current()), Some(1.0));
+
+        // Objects inside the smoke meet the index of the bottle around it
+        let (near, far) = stack.sides(3, &bead, true);
+        assert_eq!((index(near), index(far)), (Some(1.5), Some(2.0)));
+        stack.cross(3, bead.clone(), true);
+        let (near, far) = stack.sides(3, &bead, false);
+        assert_eq!((index(near), index(far)), (Some(2.0), Some(1.5)));
+        stack.cross(3, bead, false);
+
+        assert!(stack.is_invisible(2, &smoke, false, None));
+        stack.cross(2, smoke, false);
+        let (near, far) = stack.sides(1, &bottle, false);
+        assert_eq!((index(near), index(far)), (Some(1.5), None));
+    }
+}
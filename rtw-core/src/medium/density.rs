@@ -0,0 +1,153 @@
+use glam::DVec3;
+
+use crate::{Result, RtwError, utils::perlin::Perlin};
+
+/// Spatially varying density of a medium, scaling its absorption and
+/// scattering coefficients at every point in world space
+pub trait Density: Send + Sync {
+    fn density(&self, point: DVec3) -> f64;
+
+    /// Upper bound of the density anywhere. Tracking takes steps through
+    /// the medium as if it was this dense everywhere, so a tight bound keeps
+    /// it fast.
+    fn max_density(&self) -> f64;
+}
+
+/// Densities sampled on a regular 3D grid spanning a box, trilinearly
+/// interpolated in between and zero outside of the box
+pub struct DensityGrid {
+    resolution: [usize; 3],
+    /// Samples with x varying fastest, then y, then z
+    values: Vec<f64>,
+    min: DVec3,
+    max: DVec3,
+    max_density: f64,
+}
+
+impl DensityGrid {
+    pub fn new(resolution: [usize; 3], values: Vec<f64>, min: DVec3, max: DVec3) -> Result<Self> {
+        let [nx, ny, nz] = resolution;
+        if nx < 2 || ny < 2 || nz < 2 {
+            return Err(RtwError::InvalidGridResolution(nx, ny, nz));
+        }
+        if values.len() != nx * ny * nz {
+            return Err(RtwError::GridSizeMismatch(nx * ny * nz, values.len()));
+        }
+        // Tracking needs a finite upper bound and can't undo negative density
+        if let Some((i, &value)) = values
+            .iter()
+            .enumerate()
+            .find(|(_, value)| !value.is_finite() || **value < 0.0)
+        {
+            return Err(RtwError::InvalidDensity(i, value));
+        }
+        // Points are scaled into the box by its size
+        if !min.cmplt(max).all() {
+            return Err(RtwError::InvalidGridBounds);
+        }
+        let max_density = values.iter().copied().fold(0.0, f64::max);
+        Ok(Self {
+            resolution,
+            values,
+            min,
+            max,
+            max_density,
+        })
+    }
+
+    /// Reads a raw grid file: `nx * ny * nz` little endian 32 bit floats
+    /// and nothing else, with x varying fastest, then y, then z. This is
+    /// what most volume tools export as "raw float".
+    pub fn open_raw(path: &str, resolution: [usize; 3], min: DVec3, max: DVec3) -> Result<Self> {
+        let bytes = std::fs::read(path).map_err(|err| RtwError::IoError(err.to_string()))?;
+        let values = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64)
+            .collect();
+        Self::new(resolution, values, min, max)
+    }
+
+    fn value(&self, x: usize, y: usize, z: usize) -> f64 {
+        let [nx, ny, _] = self.resolution;
+        self.values[(z * ny + y) * nx + x]
+    }
+}
+
+impl Density for DensityGrid {
+    fn density(&self, point: DVec3) -> f64 {
+        let local = (point - self.min) / (self.max - self.min);
+        if local.cmplt(DVec3::ZERO).any() || local.cmpgt(DVec3::ONE).any() {
+            return 0.0;
+        }
+
+        // Samples sit on the corners of the cells, the first and last ones
+        // on the faces of the box
+        let [nx, ny, nz] = self.resolution;
+        let last = DVec3::new((nx - 1) as f64, (ny - 1) as f64, (nz - 1) as f64);
+        let p = local * last;
+        let cell = p.floor().min(last - 1.0);
+        let f = p - cell;
+        let (x, y, z) = (cell.x as usize, cell.y as usize, cell.z as usize);
+
+        let mut sum = 0.0;
+        for (dx, dy, dz) in (0..8).map(|c| (c & 1, (c >> 1) & 1, (c >> 2) & 1)) {
+            let corner = DVec3::new(dx as f64, dy as f64, dz as f64);
+            let weight = DVec3::select(corner.cmpeq(DVec3::ONE), f, 1.0 - f);
+            sum += weight.element_product() * self.value(x + dx, y + dy, z + dz);
+        }
+        sum
+    }
+
+    fn max_density(&self) -> f64 {
+        self.max_density
+    }
+}
+
+/// Billowing density from turbulent Perlin noise, for clouds and smoke
+pub struct NoiseDensity {
+    noise: Perlin,
+    /// Noise features per unit distance
+    frequency: f64,
+    octaves: u32,
+    /// Density where the noise is strongest
+    density: f64,
+    /// Noise below this is cut to empty space, larger values give sparser
+    /// and more clearly separated puffs
+    threshold: f64,
+}
+
+impl NoiseDensity {
+    pub fn new(frequency: f64, density: f64) -> Self {
+        Self {
+            noise: Perlin::default(),
+            frequency,
+            octaves: 5,
+            density,
+            threshold: 0.0,
+        }
+    }
+
+    pub fn octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.clamp(0.0, 0.99);
+        self
+    }
+}
+
+impl Density for NoiseDensity {
+    fn density(&self, point: DVec3) -> f64 {
+        let turbulence = self
+            .noise
+            .turbulence(point * self.frequency, self.octaves)
+            .min(1.0);
+        self.density * ((turbulence - self.threshold) / (1.0 - self.threshold)).max(0.0)
+    }
+
+    fn max_density(&self) -> f64 {
+        self.density
+    }
+}
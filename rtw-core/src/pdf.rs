@@ -0,0 +1,109 @@
+use glam::DVec3;
+use std::f64::consts::PI;
+
+use crate::{
+    hittable::Hittable,
+    utils::{onb::Onb, vec::random_cosine_direction},
+};
+
+/// Probability density over directions, with a way to draw from it
+pub trait Pdf {
+    /// Density of `direction` with respect to solid angle
+    fn value(&self, direction: DVec3) -> f64;
+
+    /// Random direction distributed according to this density
+    fn generate(&self) -> DVec3;
+}
+
+/// Cosine weighted hemisphere around a normal
+pub struct CosinePdf {
+    uvw: Onb,
+}
+
+impl CosinePdf {
+    pub fn new(normal: DVec3) -> Self {
+        Self {
+            uvw: Onb::new(normal),
+        }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: DVec3) -> f64 {
+        let cosine_theta = direction.normalize().dot(self.uvw.w);
+        f64::max(0.0, cosine_theta / PI)
+    }
+
+    fn generate(&self) -> DVec3 {
+        self.uvw.transform(random_cosine_direction())
+    }
+}
+
+/// Directions from `origin` towards points on some objects, usually lights
+pub struct HittablePdf<'a> {
+    objects: &'a dyn Hittable,
+    origin: DVec3,
+}
+
+impl<'a> HittablePdf<'a> {
+    pub fn new(objects: &'a dyn Hittable, origin: DVec3) -> Self {
+        Self { objects, origin }
+    }
+}
+
+impl Pdf for HittablePdf<'_> {
+    fn value(&self, direction: DVec3) -> f64 {
+        self.objects.pdf_value(self.origin, direction)
+    }
+
+    fn generate(&self) -> DVec3 {
+        self.objects.random(self.origin)
+    }
+}
+
+/// Weighted mix of several densities, drawing from each in proportion to
+/// its weight
+pub struct MixturePdf {
+    pdfs: Vec<(f64, Box<dyn Pdf>)>,
+}
+
+impl MixturePdf {
+    /// Weights don't need to sum to one, densities with no weight are
+    /// dropped
+    pub fn new(pdfs: Vec<(f64, Box<dyn Pdf>)>) -> Self {
+        let pdfs = pdfs
+            .into_iter()
+            .filter(|(weight, _)| *weight > 0.0)
+            .collect::<Vec<_>>();
+        let total = pdfs.iter().map(|(weight, _)| weight).sum::<f64>();
+        Self {
+            pdfs: pdfs
+                .into_iter()
+                .map(|(weight, pdf)| (weight / total, pdf))
+                .collect(),
+        }
+    }
+}
+
+impl Pdf for MixturePdf {
+    fn value(&self, direction: DVec3) -> f64 {
+        self.pdfs
+            .iter()
+            .map(|(weight, pdf)| weight * pdf.value(direction))
+            .sum()
+    }
+
+    fn generate(&self) -> DVec3 {
+        let mut pick = rand::random::<f64>();
+        for (weight, pdf) in &self.pdfs {
+            if pick < *weight {
+                return pdf.generate();
+            }
+            pick -= weight;
+        }
+        self.pdfs
+            .last()
+            .map(|(_, pdf)| pdf.generate())
+            .unwrap_or(DVec3::X)
+    }
+}
@@ -1,13 +1,118 @@
 use std::f64;
 
-use crate::hittable::Hittable;
+use crate::{
+    background::Background,
+    hittable::{HitRecord, Hittable},
+    material::Scatter,
+    medium::{Flight, HenyeyGreenstein, Medium, MediumStack, medium_id},
+    pdf::{HittablePdf, Pdf},
+    spectrum::SampledWavelengths,
+    texture::Footprint,
+    utils::vec::reflect,
+};
 use glam::DVec3;
 
-#[derive(Debug, Clone, Copy)]
+/// Most scattering events a path makes inside media, on top of the surface
+/// bounces. Walks in dense media get very long before reaching the surface.
+const MAX_WALK_STEPS: u32 = 1024;
+
+/// Most surfaces a shadow ray passes through on its way to a light
+const MAX_SHADOW_PASSES: u32 = 64;
+
+#[derive(Clone)]
 pub struct Ray {
     pub origin: DVec3,
     pub direction: DVec3,
     pub time: Option<f64>,
+    /// Wavelengths of a path in spectral mode, None for RGB rendering
+    pub wavelengths: Option<SampledWavelengths>,
+    /// Dielectric media the ray travels inside of
+    pub media: MediumStack,
+    /// Rays through the neighbouring pixels, for camera rays and their
+    /// specular bounces
+    pub differentials: Option<RayDifferentials>,
+}
+
+/// Two rays offset from a camera ray towards the next pixel to the right and
+/// below, as in Igehy 1999 "Tracing Ray Differentials". Where they meet the
+/// surface around a hit tells how large an area the sample stands for.
+#[derive(Debug, Clone, Copy)]
+pub struct RayDifferentials {
+    pub x_origin: DVec3,
+    pub x_direction: DVec3,
+    pub y_origin: DVec3,
+    pub y_direction: DVec3,
+}
+
+impl RayDifferentials {
+    /// Where the offset rays meet the tangent plane at `hr`, relative to the
+    /// hit point. None when either runs parallel to the plane.
+    fn offsets(&self, hr: &HitRecord) -> Option<(DVec3, DVec3)> {
+        let offset = |origin: DVec3, direction: DVec3| {
+            let t = hr.normal.dot(hr.point - origin) / hr.normal.dot(direction);
+            t.is_finite().then(|| origin + t * direction - hr.point)
+        };
+        Some((
+            offset(self.x_origin, self.x_direction)?,
+            offset(self.y_origin, self.y_direction)?,
+        ))
+    }
+
+    /// Footprint of the offset rays on the surface at `hr`, with (u, v)
+    /// derivatives from a least squares fit of them to the surface tangents
+    pub fn footprint(&self, hr: &HitRecord) -> Option<Footprint> {
+        let (dpdx, dpdy) = self.offsets(hr)?;
+
+        let (a00, a01, a11) = (
+            hr.dpdu.length_squared(),
+            hr.dpdu.dot(hr.dpdv),
+            hr.dpdv.length_squared(),
+        );
+        let inv_det = (a00 * a11 - a01 * a01).recip();
+        let inv_det = if inv_det.is_finite() { inv_det } else { 0.0 };
+        let solve = |d: DVec3| {
+            let (b0, b1) = (hr.dpdu.dot(d), hr.dpdv.dot(d));
+            let du = (a11 * b0 - a01 * b1) * inv_det;
+            let dv = (a00 * b1 - a01 * b0) * inv_det;
+            (du.clamp(-1e8, 1e8), dv.clamp(-1e8, 1e8))
+        };
+        let (dudx, dvdx) = solve(dpdx);
+        let (dudy, dvdy) = solve(dpdy);
+
+        Some(Footprint {
+            dpdx,
+            dpdy,
+            dudx,
+            dudy,
+            dvdx,
+            dvdy,
+        })
+    }
+
+    /// Offset rays after `ray` bounces off `hr` into `direction`. The
+    /// surface is taken to be flat, and transmitted rays keep the spread of
+    /// the incoming ones rather than bending by the refraction index.
+    fn bounce(&self, ray: &Ray, hr: &HitRecord, direction: DVec3) -> Option<Self> {
+        let (dpdx, dpdy) = self.offsets(hr)?;
+        let incoming = ray.direction.normalize();
+        let outgoing = direction.normalize();
+        let reflected = outgoing.dot(hr.normal) > 0.0;
+        let turn = |offset_direction: DVec3| {
+            let spread = offset_direction.normalize() - incoming;
+            let spread = if reflected {
+                reflect(spread, hr.normal)
+            } else {
+                spread
+            };
+            outgoing + spread
+        };
+        Some(Self {
+            x_origin: hr.point + dpdx,
+            x_direction: turn(self.x_direction),
+            y_origin: hr.point + dpdy,
+            y_direction: turn(self.y_direction),
+        })
+    }
 }
 
 impl Ray {
@@ -16,6 +121,9 @@ impl Ray {
             origin,
             direction,
             time: None,
+            wavelengths: None,
+            media: MediumStack::default(),
+            differentials: None,
         }
     }
 
@@ -24,30 +132,355 @@ impl Ray {
             origin,
             direction,
             time,
+            wavelengths: None,
+            media: MediumStack::default(),
+            differentials: None,
+        }
+    }
+
+    /// Ray continuing the path of this one from `origin`, at the same time
+    /// and wavelengths and inside the same media. It starts without
+    /// differentials, which only follow specular bounces.
+    pub fn scattered(&self, origin: DVec3, direction: DVec3) -> Self {
+        Self {
+            origin,
+            direction,
+            media: self.media.clone(),
+            differentials: None,
+            ..*self
         }
     }
 
+    pub fn wavelengths(mut self, wavelengths: Option<SampledWavelengths>) -> Self {
+        self.wavelengths = wavelengths;
+        self
+    }
+
+    pub fn differentials(mut self, differentials: Option<RayDifferentials>) -> Self {
+        self.differentials = differentials;
+        self
+    }
+
     pub fn at(&self, t: f64) -> DVec3 {
         t * self.direction + self.origin
     }
 
-    pub fn color<T>(&self, world: &T, depth: u32) -> DVec3
+    /// Radiance arriving along this ray, following up to `depth` bounces.
+    ///
+    /// At every bounce off a material with a scattering pdf, `lights` are
+    /// sampled directly and the material pdf is sampled to continue the
+    /// path. Emitters found by either strategy are weighted with the power
+    /// heuristic, so small lights are cheap to find while large or glossy
+    /// reflections of them don't get noisier.
+    ///
+    /// Light is absorbed and scattered along the way through dielectric
+    /// media, see [`MediumStack`] for how nested media are told apart.
+    /// Lights are sampled at scattering events in media too, through any
+    /// surfaces that don't bend light such as the boundary of a volume.
+    ///
+    /// For rays carrying wavelengths the result is spectral radiance at those
+    /// wavelengths, with the RGB colors of the scene upsampled along the way.
+    pub fn color<T>(
+        &self,
+        world: &T,
+        lights: Option<&dyn Hittable>,
+        background: &Background,
+        depth: u32,
+    ) -> DVec3
     where
         T: Hittable,
     {
-        if depth == 0 {
+        let mut radiance = DVec3::ZERO;
+        let mut throughput = DVec3::ONE;
+        let mut ray = self.clone();
+        // Density of the material sample that created `ray`, None for camera
+        // rays and specular bounces which light sampling can't produce
+        let mut scatter_pdf: Option<f64> = None;
+        // Where that sample was taken, `ray` may since have passed through
+        // surfaces that don't bend light
+        let mut scatter_origin = ray.origin;
+        let spectrum = |rgb: DVec3, ray: &Ray| match ray.wavelengths {
+            Some(wavelengths) => wavelengths.upsample(rgb),
+            None => rgb,
+        };
+
+        let mut bounces = 0;
+        let mut walk_steps = 0;
+        while bounces < depth {
+            let hit = world.hit(&ray, 0.001..f64::INFINITY);
+
+            // Light travelling through a medium is absorbed, and scattering
+            // media send it off course on the way
+            if let Some(medium) = ray.media.current().cloned() {
+                let distance = hit
+                    .as_ref()
+                    .map_or(f64::INFINITY, |hr| hr.t * ray.direction.length());
+                match medium.free_flight(&ray, distance) {
+                    Flight::Scattered { distance, weight } => {
+                        walk_steps += 1;
+                        if walk_steps > MAX_WALK_STEPS {
+                            break;
+                        }
+                        throughput *= weight;
+                        let point = ray.origin + ray.direction.normalize() * distance;
+                        let phase = HenyeyGreenstein::new(ray.direction, medium.anisotropy);
+
+                        // Lights can only be seen in a straight line from
+                        // media that don't bend light on the way out
+                        let direct = medium.refraction_index == 1.0;
+                        if direct {
+                            let f = |direction: DVec3| {
+                                (
+                                    DVec3::splat(phase.value(direction)),
+                                    ray.scattered(point, direction),
+                                )
+                            };
+                            radiance += throughput
+                                * ray.direct_light(world, lights, background, point, &phase, f);
+                        }
+
+                        // The phase function is sampled exactly, so the
+                        // throughput stays the same
+                        let direction = phase.generate();
+                        scatter_pdf = direct.then(|| phase.value(direction));
+                        scatter_origin = point;
+                        ray = ray.scattered(point, direction);
+                        continue;
+                    }
+                    Flight::Passed { weight } => throughput *= weight,
+                    Flight::Absorbed => break,
+                }
+            }
+
+            bounces += 1;
+            let Some(mut hr) = hit else {
+                let weight = match (scatter_pdf, background.pdf()) {
+                    (Some(pdf), Some(environment)) => {
+                        power_heuristic(pdf, environment.value(ray.direction))
+                    }
+                    _ => 1.0,
+                };
+                radiance += throughput * spectrum(background.value(&ray), &ray) * weight;
+                break;
+            };
+
+            hr.footprint = ray.differentials.and_then(|d| d.footprint(&hr));
+            resolve_material(&mut hr);
+
+            // Surfaces of media overlapped by a higher priority medium, or
+            // with the same index on both sides, are passed straight through
+            let boundary = hr
+                .material
+                .medium()
+                .map(|medium| (medium_id(&hr.material), medium));
+            if let Some((id, medium)) = &boundary
+                && ray.is_invisible(*id, medium, &hr)
+            {
+                let mut next = ray
+                    .scattered(hr.point, ray.direction)
+                    .differentials(ray.differentials);
+                next.media.cross(*id, medium.clone(), hr.front_face);
+                ray = next;
+                continue;
+            }
+
+            let emitted = spectrum(hr.material.emitted(&hr), &ray);
+            if emitted != DVec3::ZERO {
+                let weight = match (scatter_pdf, lights) {
+                    (Some(pdf), Some(lights)) => {
+                        power_heuristic(pdf, lights.pdf_value(scatter_origin, ray.direction))
+                    }
+                    _ => 1.0,
+                };
+                radiance += throughput * emitted * weight;
+            }
+
+            let mut next = match hr.material.scatter(&ray, &hr) {
+                None => break,
+                Some(Scatter::Specular {
+                    scattered,
+                    attenuation,
+                }) => {
+                    throughput *= spectrum(attenuation, &ray);
+                    if let (Some(current), Some(next)) = (ray.wavelengths, scattered.wavelengths) {
+                        throughput *= current.termination_weight(&next);
+                    }
+                    scatter_pdf = None;
+                    let differentials = ray
+                        .differentials
+                        .and_then(|d| d.bounce(&ray, &hr, scattered.direction));
+                    scattered.differentials(differentials)
+                }
+                Some(Scatter::Pdf(pdf)) => {
+                    let f = |direction: DVec3| {
+                        let mut shadow_ray = ray.scattered(hr.point, direction);
+                        if let Some((id, medium)) = &boundary
+                            && direction.dot(hr.normal) < 0.0
+                        {
+                            shadow_ray.media.cross(*id, medium.clone(), hr.front_face);
+                        }
+                        (hr.material.eval(&ray, &hr, direction), shadow_ray)
+                    };
+                    radiance += throughput
+                        * ray.direct_light(world, lights, background, hr.point, pdf.as_ref(), f);
+
+                    let direction = pdf.generate();
+                    let pdf_value = pdf.value(direction);
+                    if pdf_value <= 0.0 {
+                        break;
+                    }
+                    throughput *= spectrum(hr.material.eval(&ray, &hr, direction), &ray) / pdf_value;
+                    scatter_pdf = Some(pdf_value);
+                    scatter_origin = hr.point;
+                    ray.scattered(hr.point, direction)
+                }
+            };
+
+            // Transmitted rays move into or out of the medium
+            if let Some((id, medium)) = boundary
+                && next.direction.dot(hr.normal) < 0.0
+            {
+                next.media.cross(id, medium, hr.front_face);
+            }
+            ray = next;
+
+            if throughput == DVec3::ZERO {
+                break;
+            }
+        }
+
+        radiance
+    }
+
+    /// Direct light reaching `origin` from `lights` and from a background
+    /// worth sampling, each found with a direction of its own. `f` gives
+    /// the material or phase function value towards a direction, along with
+    /// the shadow ray leaving in it.
+    fn direct_light<T, F>(
+        &self,
+        world: &T,
+        lights: Option<&dyn Hittable>,
+        background: &Background,
+        origin: DVec3,
+        pdf: &dyn Pdf,
+        f: F,
+    ) -> DVec3
+    where
+        T: Hittable,
+        F: Fn(DVec3) -> (DVec3, Ray),
+    {
+        let mut light = DVec3::ZERO;
+        if let Some(lights) = lights {
+            let light_pdf = HittablePdf::new(lights, origin);
+            light += self.sample_light(world, &light_pdf, None, pdf, &f);
+        }
+        if let Some(environment) = background.pdf() {
+            light += self.sample_light(world, environment, Some(background), pdf, &f);
+        }
+        light
+    }
+
+    /// Light from a direction drawn from `light_pdf`, weighted against the
+    /// chance of `pdf` picking the same direction. The shadow ray sees
+    /// emitters, or only `background` when given.
+    fn sample_light<T, F>(
+        &self,
+        world: &T,
+        light_pdf: &dyn Pdf,
+        background: Option<&Background>,
+        pdf: &dyn Pdf,
+        f: F,
+    ) -> DVec3
+    where
+        T: Hittable,
+        F: Fn(DVec3) -> (DVec3, Ray),
+    {
+        let direction = light_pdf.generate();
+        let light_pdf_value = light_pdf.value(direction);
+        if light_pdf_value <= 0.0 {
             return DVec3::ZERO;
         }
 
-        if let Some(hr) = world.hit(self, 0.001..f64::INFINITY) {
-            if let Some(scatter) = hr.material.scatter(self, &hr) {
-                return scatter.attenuation * Ray::color(&scatter.scattered, world, depth - 1);
-            }
+        let (f, shadow_ray) = f(direction);
+        if f == DVec3::ZERO {
             return DVec3::ZERO;
         }
+        let f = match self.wavelengths {
+            Some(wavelengths) => wavelengths.upsample(f),
+            None => f,
+        };
 
-        let unit_dir = self.direction.normalize();
-        let a = 0.5 * (unit_dir.y + 1.0);
-        DVec3::lerp(DVec3::ONE, DVec3::new(0.5, 0.7, 1.0), a)
+        let weight = power_heuristic(light_pdf_value, pdf.value(direction));
+        f * shadow_ray.light(world, background) * weight / light_pdf_value
     }
+
+    /// Light from the first emitter along this shadow ray, or from
+    /// `background` when given and the ray escapes, dimmed by the media on
+    /// the way. Surfaces that don't bend light are passed through, anything
+    /// else blocks the light.
+    fn light<T>(mut self, world: &T, background: Option<&Background>) -> DVec3
+    where
+        T: Hittable,
+    {
+        let mut transmittance = DVec3::ONE;
+        for _ in 0..MAX_SHADOW_PASSES {
+            let hit = world.hit(&self, 0.001..f64::INFINITY);
+            if let Some(medium) = self.media.current() {
+                let distance = hit
+                    .as_ref()
+                    .map_or(f64::INFINITY, |hr| hr.t * self.direction.length());
+                transmittance *= medium.transmittance(&self, distance);
+            }
+            let Some(mut hr) = hit else {
+                let escaped = background.map_or(DVec3::ZERO, |background| background.value(&self));
+                return transmittance
+                    * match self.wavelengths {
+                        Some(wavelengths) => wavelengths.upsample(escaped),
+                        None => escaped,
+                    };
+            };
+
+            resolve_material(&mut hr);
+            if let Some(medium) = hr.material.medium() {
+                let id = medium_id(&hr.material);
+                if self.is_invisible(id, &medium, &hr) {
+                    let mut next = self.scattered(hr.point, self.direction);
+                    next.media.cross(id, medium, hr.front_face);
+                    self = next;
+                    continue;
+                }
+            }
+
+            if background.is_some() {
+                return DVec3::ZERO;
+            }
+            let emitted = hr.material.emitted(&hr);
+            return transmittance
+                * match self.wavelengths {
+                    Some(wavelengths) => wavelengths.upsample(emitted),
+                    None => emitted,
+                };
+        }
+        DVec3::ZERO
+    }
+
+    /// Whether this ray crosses the surface of `medium` at `hr` unchanged
+    fn is_invisible(&self, id: usize, medium: &Medium, hr: &HitRecord) -> bool {
+        let hero = self.wavelengths.map(|wavelengths| wavelengths.hero());
+        self.media.is_invisible(id, medium, hr.front_face, hero)
+    }
+}
+
+/// Replaces the material of `hr` with the one it picks for this hit
+fn resolve_material(hr: &mut HitRecord) {
+    while let Some(material) = hr.material.select(hr) {
+        hr.material = material;
+    }
+}
+
+/// Multiple importance sampling weight of a sample drawn with density `a`
+/// when another strategy could have drawn it with density `b`
+fn power_heuristic(a: f64, b: f64) -> f64 {
+    let (a2, b2) = (a * a, b * b);
+    if a2 + b2 == 0.0 { 0.0 } else { a2 / (a2 + b2) }
 }
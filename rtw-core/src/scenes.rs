@@ -1,14 +1,48 @@
-use glam::DVec3;
+//! Scenes that read images or tables expect to run from the repository
+//! root, where their files live in `assets/`.
+
+use glam::{DQuat, DVec2, DVec3};
 use rand::Rng;
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use crate::{
     RenderConfig, Result,
-    camera::Camera,
-    hittable::{bvh_node::BvhNode, list::HittableList, sphere::Sphere},
-    material::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal},
-    texture::checker::Checker,
-    utils,
+    animation::{Animation, CameraKey},
+    background::{Background, environment::EnvironmentMap, sun_sky::SunSky},
+    camera::{Camera, CameraPose, ShutterCurve},
+    hittable::{
+        animated::{Animated, Transform},
+        bvh_node::BvhNode,
+        heightfield::Heightfield,
+        list::HittableList,
+        masked::{Cutout, Masked},
+        quad::{Quad, quad_box},
+        sphere::Sphere,
+    },
+    material::{
+        Material, coated::Coated, conductor::Conductor, dielectric::{Dielectric, Dispersion},
+        diffuse_light::DiffuseLight, lambertian::Lambertian, measured::Measured, metal::Metal,
+        mix::MixMaterial, normal_map::NormalMapped, principled::Principled,
+        rough_dielectric::RoughDielectric, subsurface::Subsurface, volume::Volume,
+    },
+    medium::density::{DensityGrid, NoiseDensity},
+    texture::{
+        Texture,
+        checker::{Checker, UvChecker},
+        image::{Filter, ImageTexture},
+        node::{Add, Invert, Mix, Multiply, Ramp, Remap},
+        noise::{Marble, NoiseTexture, Turbulence, Wood},
+        pattern::{Dots, Gradient, Grid, Stripes},
+        ramp::ColorRamp,
+        solid::SolidColor,
+        transform::{SolidTransform, UvTransform},
+        triplanar::Triplanar,
+        worley::{Cellular, Worley},
+    },
+    utils::{
+        self,
+        keyframe::{Interpolation, Keyframes},
+    },
 };
 
 pub(crate) fn test_scene(config: &RenderConfig) -> Result<()> {
@@ -26,13 +60,10 @@ pub(crate) fn test_scene(config: &RenderConfig) -> Result<()> {
 
     let material_center = Arc::new(Lambertian::from_color(DVec3::new(0.1, 0.2, 0.5)));
 
-    let material_left = Arc::new(Dielectric {
-        refraction_index: 1.5,
-    });
+    let material_left = Arc::new(Dielectric::new(1.5));
 
-    let material_bubble = Arc::new(Dielectric {
-        refraction_index: 1.00 / 1.50,
-    });
+    // Air inside the glass, overriding it where they overlap
+    let material_bubble = Arc::new(Dielectric::new(1.0).priority(1));
 
     let material_right = Arc::new(Metal {
         albedo: DVec3::new(0.8, 0.6, 0.2),
@@ -99,7 +130,7 @@ pub(crate) fn random_scene(config: &RenderConfig) -> Result<()> {
                 if mat_choice < 0.8 {
                     let mat = Lambertian::from_color(utils::vec::random() * utils::vec::random());
                     let rand_num = rng.random_range(0.0..0.5);
-                    let center2 = DVec3::new(0.0, rand_num, 0.0);
+                    let center2 = center + DVec3::new(0.0, rand_num, 0.0);
                     world.add(Arc::new(Sphere::moving(
                         center,
                         center2,
@@ -112,18 +143,14 @@ pub(crate) fn random_scene(config: &RenderConfig) -> Result<()> {
                     let mat = Metal { albedo, fuzz };
                     world.add(Arc::new(Sphere::stationary(center, 0.2, Arc::new(mat))?));
                 } else {
-                    let mat = Dielectric {
-                        refraction_index: 1.5,
-                    };
+                    let mat = Dielectric::new(1.5);
                     world.add(Arc::new(Sphere::stationary(center, 0.2, Arc::new(mat))?));
                 }
             }
         }
     }
 
-    let mat1 = Dielectric {
-        refraction_index: 1.5,
-    };
+    let mat1 = Dielectric::new(1.5);
     world.add(Arc::new(Sphere::stationary(
         DVec3::new(0.0, 1.0, 0.0),
         1.0,
@@ -164,3 +191,1554 @@ pub(crate) fn random_scene(config: &RenderConfig) -> Result<()> {
 
     cam.render(&world, config)
 }
+
+pub(crate) fn terrain_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let ridges = |u: f64, v: f64| {
+        let (x, z) = (u * std::f64::consts::TAU, v * std::f64::consts::TAU);
+        let h = 0.5
+            + 0.25 * (3.0 * x).sin() * (2.0 * z).cos()
+            + 0.125 * (7.0 * x + 5.0 * z).sin()
+            + 0.0625 * (17.0 * x - 13.0 * z).cos();
+        h.clamp(0.0, 1.0)
+    };
+    let material_ground = Lambertian::from_color(DVec3::new(0.45, 0.4, 0.3));
+    world.add(Arc::new(Heightfield::from_fn(
+        DVec3::new(-10.0, -1.0, -10.0),
+        DVec3::new(20.0, 3.0, 20.0),
+        512,
+        512,
+        ridges,
+        Arc::new(material_ground),
+    )?));
+
+    // Volcano from a 16 bit height map, rising behind the far edge
+    let material_volcano = Lambertian::from_color(DVec3::new(0.3, 0.25, 0.25));
+    world.add(Arc::new(Heightfield::from_image(
+        "assets/heightmap.png",
+        DVec3::new(-7.0, -1.5, -23.0),
+        DVec3::new(10.0, 7.0, 10.0),
+        Arc::new(material_volcano),
+    )?));
+
+    let material_sphere = Arc::new(Metal {
+        albedo: DVec3::new(0.7, 0.6, 0.5),
+        fuzz: 0.0,
+    });
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, 3.0, 0.0),
+        1.0,
+        material_sphere,
+    )?));
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(800)
+        .samples_per_pixel(50)
+        .max_depth(20)
+        .vfov(40.0)
+        .look_from(DVec3::new(12.0, 6.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn earth_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    // Same map with both filters, nearest on the left and bilinear on the right
+    for (x, filter) in [(-2.2, Filter::Nearest), (2.2, Filter::Bilinear)] {
+        let earth_texture = ImageTexture::open("assets/earthmap.png", filter)?;
+        let earth_surface = Lambertian::from_texture(Arc::new(earth_texture));
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(x, 0.0, 0.0),
+            2.0,
+            Arc::new(earth_surface),
+        )?));
+    }
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(30.0)
+        .look_from(DVec3::new(0.0, 0.0, 12.0))
+        .look_at(DVec3::new(0.0, 0.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn noise_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    // Pale oak floor with tight rings
+    let oak = Wood::new(1.5)
+        .octaves(4)
+        .distortion(0.8)
+        .ramp(ColorRamp::new(vec![
+            (0.0, DVec3::new(0.8, 0.62, 0.42)),
+            (0.8, DVec3::new(0.72, 0.52, 0.32)),
+            (1.0, DVec3::new(0.5, 0.33, 0.18)),
+        ]));
+    world.add(Arc::new(Quad::new(
+        DVec3::new(-20.0, 0.0, 10.0),
+        DVec3::new(40.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, -30.0),
+        Arc::new(Lambertian::from_texture(Arc::new(oak))),
+    )?));
+
+    let textures: [Arc<dyn Texture>; 4] = [
+        Arc::new(NoiseTexture::new(4.0).ramp(ColorRamp::between(
+            DVec3::new(0.1, 0.2, 0.5),
+            DVec3::new(0.9, 0.9, 0.8),
+        ))),
+        Arc::new(
+            Turbulence::new(2.0)
+                .octaves(5)
+                .ramp(ColorRamp::between(DVec3::new(0.9, 0.8, 0.6), DVec3::new(0.3, 0.1, 0.05))),
+        ),
+        // Green marble with thin pale veins
+        Arc::new(
+            Marble::new(4.0)
+                .octaves(6)
+                .distortion(6.0)
+                .ramp(ColorRamp::new(vec![
+                    (0.0, DVec3::new(0.02, 0.12, 0.06)),
+                    (0.85, DVec3::new(0.05, 0.3, 0.15)),
+                    (1.0, DVec3::new(0.85, 0.9, 0.85)),
+                ])),
+        ),
+        Arc::new(Wood::new(3.0)),
+    ];
+    for (i, texture) in textures.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(-3.3 + 2.2 * i as f64, 1.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::from_texture(texture)),
+        )?));
+    }
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-3.0, 6.0, -1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(3.0, 3.0, 3.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .lights(light)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn sunsky_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let ground = Lambertian::from_color(DVec3::new(0.5, 0.45, 0.4));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(ground),
+    )?));
+
+    let materials: [Arc<dyn Material>; 4] = [
+        Arc::new(Lambertian::from_color(DVec3::new(0.8, 0.3, 0.2))),
+        Arc::new(Conductor::gold(0.2)),
+        Arc::new(Dielectric::new(1.5)),
+        Arc::new(Metal {
+            albedo: DVec3::splat(0.9),
+            fuzz: 0.0,
+        }),
+    ];
+    for (i, material) in materials.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(-3.3 + 2.2 * i as f64, 1.0, 0.0),
+            1.0,
+            material,
+        )?));
+    }
+
+    // Afternoon in Munich at midsummer, the sun to the west
+    let sky = SunSky::from_time(48.1, 11.6, 172, 15.5)
+        .turbidity(3.0)
+        .ground_albedo(DVec3::new(0.3, 0.28, 0.25))
+        .intensity(1.0);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .background(Background::SunSky(Arc::new(sky)))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn environment_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let ground = Lambertian::from_texture(Arc::new(Checker::from_colors(
+        1.0,
+        DVec3::new(0.2, 0.3, 0.1),
+        DVec3::new(0.9, 0.9, 0.9),
+    )));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(ground),
+    )?));
+
+    let materials: [Arc<dyn Material>; 4] = [
+        Arc::new(Lambertian::from_color(DVec3::new(0.8, 0.3, 0.2))),
+        Arc::new(Conductor::gold(0.2)),
+        Arc::new(Dielectric::new(1.5)),
+        Arc::new(Metal {
+            albedo: DVec3::splat(0.9),
+            fuzz: 0.0,
+        }),
+    ];
+    for (i, material) in materials.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(-3.3 + 2.2 * i as f64, 1.0, 0.0),
+            1.0,
+            material,
+        )?));
+    }
+
+    // Lit by the sky and sun of the map alone, which direct light sampling
+    // finds through the map
+    let sky = EnvironmentMap::open("assets/sky.hdr")?
+        .rotation(1.2)
+        .intensity(0.3);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .background(Background::Environment(Arc::new(sky)))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn nodes_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    // Checker ground dimmed in patches by turbulence
+    let grime = Remap::new(Arc::new(Turbulence::new(0.8)), 0.0..1.0, 0.5..1.0);
+    let ground = Multiply::new(
+        Arc::new(Checker::from_colors(
+            1.0,
+            DVec3::new(0.2, 0.3, 0.1),
+            DVec3::new(0.9, 0.9, 0.9),
+        )),
+        Arc::new(grime),
+    );
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(ground))),
+    )?));
+
+    // Noise with its contrast raised, then colored
+    let contrast = Remap::new(Arc::new(NoiseTexture::new(3.0)), 0.35..0.65, 0.0..1.0).clamp();
+    let lava = Ramp::new(
+        Arc::new(contrast),
+        ColorRamp::new(vec![
+            (0.0, DVec3::new(0.05, 0.02, 0.02)),
+            (0.6, DVec3::new(0.8, 0.15, 0.02)),
+            (1.0, DVec3::new(1.0, 0.8, 0.2)),
+        ]),
+    );
+
+    // Marble and wood cells
+    let cells = Worley::new(0.1, Cellular::Cells, ColorRamp::between(DVec3::ZERO, DVec3::ONE));
+    let patchwork = Mix::new(
+        Arc::new(Marble::new(4.0)),
+        Arc::new(Wood::new(6.0)),
+        Arc::new(Remap::new(Arc::new(cells), 0.5..0.5001, 0.0..1.0).clamp()),
+    );
+
+    // Checker darkened where turbulence is high
+    let dirty = Multiply::new(
+        Arc::new(UvChecker::from_colors(
+            0.1,
+            DVec3::new(0.1, 0.4, 0.8),
+            DVec3::new(0.9, 0.9, 0.8),
+        )),
+        Arc::new(Invert::new(Arc::new(Turbulence::new(2.0).octaves(4)))),
+    );
+
+    // Bright cracks added over a dark base
+    let cracks = Ramp::new(
+        Arc::new(Invert::new(Arc::new(Worley::new(
+            0.08,
+            Cellular::Edges,
+            ColorRamp::between(DVec3::ZERO, DVec3::ONE),
+        )))),
+        ColorRamp::new(vec![
+            (0.85, DVec3::ZERO),
+            (1.0, DVec3::new(0.2, 0.9, 0.6)),
+        ]),
+    );
+    let cracked = Add::new(Arc::new(SolidColor::new(DVec3::splat(0.08))), Arc::new(cracks));
+
+    let textures: [Arc<dyn Texture>; 4] = [
+        Arc::new(lava),
+        Arc::new(patchwork),
+        Arc::new(dirty),
+        Arc::new(cracked),
+    ];
+    for (i, texture) in textures.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(-3.3 + 2.2 * i as f64, 1.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::from_texture(texture)),
+        )?));
+    }
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn mapping_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let bricks = Arc::new(ImageTexture::open("assets/bricks.png", Filter::Ewa)?.repeat());
+
+    // Bricks tiled across the floor at an angle
+    let floor = UvTransform::new(bricks.clone())
+        .scale(DVec2::splat(16.0))
+        .rotation(std::f64::consts::FRAC_PI_4)
+        .offset(DVec2::new(0.25, 0.0));
+    world.add(Arc::new(Quad::new(
+        DVec3::new(-20.0, 0.0, 20.0),
+        DVec3::new(40.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, -40.0),
+        Arc::new(Lambertian::from_texture(Arc::new(floor))),
+    )?));
+
+    // Checker squashed into bands and tilted
+    let bands = SolidTransform::new(Arc::new(Checker::from_colors(
+        1.0,
+        DVec3::new(0.9, 0.85, 0.2),
+        DVec3::new(0.1, 0.3, 0.6),
+    )))
+    .scale(DVec3::new(0.5, 4.0, 0.5))
+    .rotation(DQuat::from_rotation_z(0.4))
+    .translation(DVec3::splat(0.5));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(-3.3, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::from_texture(Arc::new(bands))),
+    )?));
+
+    // Sphere (u, v) pinch at the poles, which triplanar mapping avoids
+    let uv_mapped = UvTransform::new(bricks.clone()).scale(DVec2::new(4.0, 2.0));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(-1.1, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::from_texture(Arc::new(uv_mapped))),
+    )?));
+    let triplanar = Arc::new(Triplanar::new(bricks, 1.0).sharpness(8.0));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(1.1, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::from_texture(triplanar.clone())),
+    )?));
+
+    let cube = quad_box(
+        DVec3::splat(-0.7),
+        DVec3::splat(0.7),
+        Arc::new(Lambertian::from_texture(triplanar)),
+    )?;
+    let placement = Transform::translation(DVec3::new(3.3, 1.0, 0.0))
+        .rotation(DQuat::from_rotation_y(0.6) * DQuat::from_rotation_x(0.5));
+    world.add(Arc::new(Animated::new(
+        Arc::new(cube),
+        Keyframes::constant(placement),
+    )?));
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn filtering_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    // Strips running to the horizon, tiled with cells of 0.5 and filtered
+    // through the footprint of each sample, except for the nearest lookup
+    // left aliased for comparison
+    let checker = Checker::from_colors(
+        0.5,
+        DVec3::new(0.92, 0.88, 0.78),
+        DVec3::new(0.6, 0.2, 0.1),
+    );
+    let textures: [Arc<dyn Texture>; 4] = [
+        Arc::new(checker),
+        Arc::new(ImageTexture::open("assets/tiles.png", Filter::Nearest)?),
+        Arc::new(ImageTexture::open("assets/tiles.png", Filter::Trilinear)?),
+        Arc::new(ImageTexture::open("assets/tiles.png", Filter::Ewa)?),
+    ];
+    for (i, texture) in textures.into_iter().enumerate() {
+        world.add(Arc::new(Quad::new(
+            DVec3::new(-8.0 + 4.0 * i as f64, 0.0, 0.0),
+            DVec3::new(4.0, 0.0, 0.0),
+            DVec3::new(0.0, 0.0, -80.0),
+            Arc::new(Lambertian::from_texture(texture)),
+        )?));
+    }
+
+    // A mirror keeps the footprint of the rays it reflects
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, 1.0, -6.0),
+        1.0,
+        Arc::new(Metal {
+            albedo: DVec3::splat(0.9),
+            fuzz: 0.0,
+        }),
+    )?));
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(4)
+        .max_depth(10)
+        .vfov(40.0)
+        .look_from(DVec3::new(0.0, 1.5, 4.0))
+        .look_at(DVec3::new(0.0, 0.5, -10.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn patterns_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let white = DVec3::new(0.9, 0.9, 0.9);
+    let ink = DVec3::new(0.1, 0.1, 0.15);
+    let sunset = ColorRamp::new(vec![
+        (0.0, DVec3::new(0.2, 0.05, 0.3)),
+        (0.5, DVec3::new(0.9, 0.3, 0.1)),
+        (1.0, DVec3::new(1.0, 0.9, 0.4)),
+    ]);
+    let stone = ColorRamp::between(DVec3::new(0.15, 0.15, 0.15), DVec3::new(0.75, 0.7, 0.65));
+
+    // Each panel spans (u, v) in [0, 1] once
+    let textures: [Arc<dyn Texture>; 9] = [
+        Arc::new(UvChecker::new(
+            0.25,
+            Arc::new(Dots::from_colors(0.0625, ink, white)),
+            Arc::new(
+                Stripes::from_colors(0.05, DVec3::new(0.8, 0.1, 0.1), white)
+                    .angle(std::f64::consts::FRAC_PI_4)
+                    .width(0.3),
+            ),
+        )),
+        Arc::new(Stripes::from_colors(0.2, DVec3::new(0.1, 0.3, 0.7), white)),
+        Arc::new(Grid::from_colors(0.125, ink, DVec3::new(0.85, 0.85, 0.75)).line_width(0.1)),
+        Arc::new(
+            Dots::new(
+                0.2,
+                Arc::new(SolidColor::new(white)),
+                Arc::new(Gradient::radial(1.0, sunset.clone())),
+            )
+            .radius(0.25)
+            .staggered(),
+        ),
+        Arc::new(Gradient::linear(1.0, std::f64::consts::FRAC_PI_4 / 2.0, sunset.clone())),
+        Arc::new(Worley::new(0.2, Cellular::Distance, stone.clone())),
+        Arc::new(Worley::new(0.15, Cellular::Edges, ColorRamp::between(ink, white))),
+        Arc::new(Worley::new(0.15, Cellular::Cells, sunset).jitter(0.8)),
+        Arc::new(UvChecker::from_colors(0.125, ink, white)),
+    ];
+    for (i, texture) in textures.into_iter().enumerate() {
+        let (column, row) = ((i % 3) as f64, (i / 3) as f64);
+        world.add(Arc::new(Quad::new(
+            DVec3::new(-3.2 + 2.2 * column, 4.6 - 2.2 * row, 0.0),
+            DVec3::new(2.0, 0.0, 0.0),
+            DVec3::new(0.0, 2.0, 0.0),
+            Arc::new(Lambertian::from_texture(texture)),
+        )?));
+    }
+
+    let cam = Camera::init()
+        .aspect_ratio(1.0)
+        .image_width(600)
+        .samples_per_pixel(50)
+        .max_depth(10)
+        .vfov(40.0)
+        .look_from(DVec3::new(0.0, 3.3, 10.0))
+        .look_at(DVec3::new(0.0, 3.3, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn measured_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Checker::from_colors(0.5, DVec3::new(0.2, 0.2, 0.2), DVec3::new(0.8, 0.8, 0.8));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(checker))),
+    )?));
+
+    // Measured aluminium from the MERL database in the middle, between the
+    // analytic models meant to match it. The table is too large to keep in
+    // the repository, download `aluminium.binary` from
+    // https://www.merl.com/brdf/ into `assets/`. Without it the middle
+    // sphere falls back to the analytic conductor.
+    let table = "assets/aluminium.binary";
+    let measured: Arc<dyn Material> = if Path::new(table).exists() {
+        Arc::new(Measured::open_merl(table)?)
+    } else {
+        eprintln!("{table} not found, rendering analytic aluminium in its place");
+        Arc::new(Conductor::aluminium(0.3))
+    };
+    let materials: [Arc<dyn Material>; 3] = [
+        Arc::new(Metal {
+            albedo: DVec3::new(0.91, 0.92, 0.92),
+            fuzz: 0.2,
+        }),
+        measured,
+        Arc::new(Conductor::aluminium(0.3)),
+    ];
+    for (i, material) in materials.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(-2.4 + 2.4 * i as f64, 1.0, 0.0),
+            1.0,
+            material,
+        )?));
+    }
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-3.0, 6.0, -1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(3.0, 3.0, 3.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .lights(light)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn volumes_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_color(DVec3::new(0.5, 0.5, 0.5))),
+    )?));
+
+    // Cloud of turbulent noise, scattering mostly forwards
+    let cloud = Volume::new(DVec3::splat(4.0), DVec3::ZERO)
+        .density(Arc::new(NoiseDensity::new(1.2, 1.0).octaves(4).threshold(0.15)))
+        .anisotropy(0.6)
+        .priority(1);
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(-2.5, 2.0, 0.0),
+        1.5,
+        Arc::new(cloud),
+    )?));
+
+    // Smoke plume from a raw density grid filling the box
+    let (min, max) = (DVec3::new(1.0, -0.2, -1.0), DVec3::new(4.0, 4.5, 1.0));
+    let plume = DensityGrid::open_raw("assets/smoke.raw", [32, 48, 32], min, max)?;
+    let smoke = Volume::new(DVec3::splat(3.0), DVec3::splat(1.0))
+        .density(Arc::new(plume))
+        .anisotropy(0.3)
+        .priority(1);
+    world.add(Arc::new(quad_box(min, max, Arc::new(smoke))?));
+
+    // Thin haze around everything, showing shafts of light cut by slats
+    // under a long and narrow light
+    let haze = Volume::new(DVec3::splat(0.02), DVec3::ZERO).anisotropy(0.5);
+    world.add(Arc::new(quad_box(
+        DVec3::new(-8.0, -0.5, -6.0),
+        DVec3::new(8.0, 10.0, 6.0),
+        Arc::new(haze),
+    )?));
+    let slat = Arc::new(Lambertian::from_color(DVec3::new(0.2, 0.2, 0.2)));
+    for i in 0..6 {
+        world.add(Arc::new(Quad::new(
+            DVec3::new(-3.0 + 1.0 * i as f64, 7.5, -3.0),
+            DVec3::new(0.3, 0.0, 0.0),
+            DVec3::new(0.0, 0.0, 6.0),
+            slat.clone(),
+        )?));
+    }
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-0.15, 11.0, -3.0),
+        DVec3::new(0.3, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 6.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(80.0, 80.0, 80.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .background(Background::Solid(DVec3::new(0.01, 0.01, 0.015)))
+        .lights(light)
+        .vfov(40.0)
+        .look_from(DVec3::new(0.0, 3.0, 14.0))
+        .look_at(DVec3::new(0.0, 3.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn bump_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    // Tangent space normal map on a metal floor, which shows the tilted
+    // normals in its reflections
+    let normals = ImageTexture::open_raw("assets/normalmap.png", Filter::Bilinear)?;
+    let floor = NormalMapped::normal_map(Arc::new(Conductor::aluminium(0.2)), Arc::new(normals));
+    world.add(Arc::new(Quad::new(
+        DVec3::new(-8.0, 0.0, 4.0),
+        DVec3::new(16.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, -10.0),
+        Arc::new(floor),
+    )?));
+
+    // Same height map under a diffuse, a metallic and a coated surface
+    let height: Arc<dyn Texture> =
+        Arc::new(ImageTexture::open_raw("assets/bumpmap.png", Filter::Bilinear)?);
+    let bases: [Arc<dyn Material>; 3] = [
+        Arc::new(Lambertian::from_color(DVec3::new(0.7, 0.5, 0.3))),
+        Arc::new(Conductor::gold(0.2)),
+        Arc::new(Coated::new(
+            Arc::new(Lambertian::from_color(DVec3::new(0.1, 0.2, 0.5))),
+            1.5,
+        )),
+    ];
+    for (i, base) in bases.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(-2.4 + 2.4 * i as f64, 1.0, 0.0),
+            1.0,
+            Arc::new(NormalMapped::bump(base, height.clone(), 0.05)),
+        )?));
+    }
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-3.0, 6.0, -1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(3.0, 3.0, 3.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .lights(light)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn cutout_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let ground = Lambertian::from_color(DVec3::new(0.4, 0.5, 0.3));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(ground),
+    )?));
+
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, 1.0, -2.0),
+        1.0,
+        Arc::new(Lambertian::from_color(DVec3::new(0.7, 0.2, 0.1))),
+    )?));
+
+    // Fence with square holes, cut by a spatial checker
+    let holes = Checker::from_colors(0.15, DVec3::ONE, DVec3::ZERO);
+    let fence = Quad::new(
+        DVec3::new(-4.0, 0.0, 0.5),
+        DVec3::new(8.0, 0.0, 0.0),
+        DVec3::new(0.0, 2.5, 0.0),
+        Arc::new(Conductor::aluminium(0.3)),
+    )?;
+    world.add(Arc::new(Masked::new(Arc::new(fence), Arc::new(holes))));
+
+    // Leaves on quads, colored and cut out by the same image. The soft edge
+    // of the alpha channel is kept stochastically.
+    let color = Arc::new(ImageTexture::open("assets/leaf.png", Filter::Bilinear)?);
+    let opacity = Arc::new(ImageTexture::open_alpha("assets/leaf.png", Filter::Bilinear)?);
+    let leaf_material = Arc::new(Lambertian::from_texture(color));
+    let mut rng = rand::rng();
+    for i in 0..12 {
+        let corner = DVec3::new(
+            -3.5 + 0.6 * i as f64,
+            2.6 + rng.random_range(0.0..0.8),
+            rng.random_range(0.5..1.5),
+        );
+        let leaf = Quad::new(
+            corner,
+            DVec3::new(0.8, 0.0, rng.random_range(-0.3..0.3)),
+            DVec3::new(rng.random_range(-0.3..0.3), 0.8, 0.2),
+            leaf_material.clone(),
+        )?;
+        world.add(Arc::new(
+            Masked::new(Arc::new(leaf), opacity.clone()).cutout(Cutout::Stochastic),
+        ));
+    }
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-3.0, 6.0, 1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(3.0, 3.0, 3.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .lights(light)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.5, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn motion_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Checker::from_colors(0.5, DVec3::new(0.2, 0.3, 0.1), DVec3::new(0.9, 0.9, 0.9));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(checker))),
+    )?));
+
+    // Two keyframe sphere, bouncing up while the shutter is open
+    world.add(Arc::new(Sphere::moving(
+        DVec3::new(-2.5, 0.5, 0.0),
+        DVec3::new(-2.5, 1.2, 0.0),
+        0.5,
+        Arc::new(Lambertian::from_color(DVec3::new(0.7, 0.2, 0.1))),
+    )?));
+
+    // Stationary reference
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, 0.5, 0.0),
+        0.5,
+        Arc::new(Metal {
+            albedo: DVec3::new(0.7, 0.6, 0.5),
+            fuzz: 0.0,
+        }),
+    )?));
+
+    // Off-center sphere swinging around the y axis along several keyframes
+    let swinging = Arc::new(Sphere::stationary(
+        DVec3::new(0.8, 0.0, 0.0),
+        0.4,
+        Arc::new(Lambertian::from_color(DVec3::new(0.1, 0.3, 0.7))),
+    )?);
+    let base = Transform::translation(DVec3::new(2.5, 0.4, 0.0));
+    let swing = Keyframes::new(vec![
+        (0.0, base),
+        (0.3, base.rotation(DQuat::from_rotation_y(0.6))),
+        (0.6, base.rotation(DQuat::from_rotation_y(-0.3)).scale(1.2)),
+        (1.0, base.rotation(DQuat::from_rotation_y(0.9))),
+    ])?;
+    world.add(Arc::new(Animated::new(swinging, swing)?));
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .vfov(30.0)
+        .pose_keyframe(0.0, DVec3::new(0.0, 2.0, 10.0), DVec3::new(0.0, 0.5, 0.0))
+        .pose_keyframe(1.0, DVec3::new(0.3, 2.0, 10.0), DVec3::new(0.0, 0.5, 0.0))
+        .shutter(0.0, 1.0)
+        .shutter_curve(ShutterCurve::Triangle)
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn turntable_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Checker::from_colors(0.5, DVec3::new(0.2, 0.3, 0.1), DVec3::new(0.9, 0.9, 0.9));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(checker))),
+    )?));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new(1.5)),
+    )?));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(-2.2, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::from_color(DVec3::new(0.4, 0.2, 0.1))),
+    )?));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(2.2, 1.0, 0.0),
+        1.0,
+        Arc::new(Metal {
+            albedo: DVec3::new(0.7, 0.6, 0.5),
+            fuzz: 0.0,
+        }),
+    )?));
+
+    let world = BvhNode::from(world);
+
+    // Full orbit around the spheres, swooping in closer on the far side
+    let orbit = (0..=8)
+        .map(|i| {
+            let angle = i as f64 * std::f64::consts::TAU / 8.0;
+            let radius = if i % 4 == 2 { 7.0 } else { 11.0 };
+            let look_from = DVec3::new(radius * angle.sin(), 2.5, radius * angle.cos());
+            let key = CameraKey {
+                pose: CameraPose {
+                    look_from,
+                    look_at: DVec3::new(0.0, 1.0, 0.0),
+                },
+                vfov: if i == 4 { 35.0 } else { 30.0 },
+                focus_distance: (look_from - DVec3::new(0.0, 1.0, 0.0)).length(),
+            };
+            (i as f64 / 8.0, key)
+        })
+        .collect();
+    let path = Keyframes::new(orbit)?.interpolation(Interpolation::CatmullRom);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(400)
+        .samples_per_pixel(50)
+        .max_depth(20)
+        .vup(DVec3::new(0.0, 1.0, 0.0));
+
+    Animation::new(config.frames.unwrap_or(48), path)
+        .shutter_angle(180.0)
+        .render(&cam, &world, config)
+}
+
+pub(crate) fn cornell_box(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let red = Arc::new(Lambertian::from_color(DVec3::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::from_color(DVec3::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::from_color(DVec3::new(0.12, 0.45, 0.15)));
+    // Just below the ceiling, which it would light up from behind
+    let light = Arc::new(DiffuseLight::from_color(DVec3::new(15.0, 15.0, 15.0)).one_sided());
+
+    world.add(Arc::new(Quad::new(
+        DVec3::new(555.0, 0.0, 0.0),
+        DVec3::new(0.0, 555.0, 0.0),
+        DVec3::new(0.0, 0.0, 555.0),
+        green,
+    )?));
+    world.add(Arc::new(Quad::new(
+        DVec3::new(0.0, 0.0, 0.0),
+        DVec3::new(0.0, 555.0, 0.0),
+        DVec3::new(0.0, 0.0, 555.0),
+        red,
+    )?));
+    let ceiling_light = Arc::new(Quad::new(
+        DVec3::new(343.0, 554.0, 332.0),
+        DVec3::new(-130.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, -105.0),
+        light,
+    )?);
+    world.add(ceiling_light.clone());
+    world.add(Arc::new(Quad::new(
+        DVec3::new(0.0, 0.0, 0.0),
+        DVec3::new(555.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 555.0),
+        white.clone(),
+    )?));
+    world.add(Arc::new(Quad::new(
+        DVec3::new(555.0, 555.0, 555.0),
+        DVec3::new(-555.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, -555.0),
+        white.clone(),
+    )?));
+    world.add(Arc::new(Quad::new(
+        DVec3::new(0.0, 0.0, 555.0),
+        DVec3::new(555.0, 0.0, 0.0),
+        DVec3::new(0.0, 555.0, 0.0),
+        white.clone(),
+    )?));
+
+    let tall_box = quad_box(DVec3::ZERO, DVec3::new(165.0, 330.0, 165.0), white.clone())?;
+    let tall_box_placement = Transform::translation(DVec3::new(265.0, 0.0, 295.0))
+        .rotation(DQuat::from_rotation_y(15f64.to_radians()));
+    world.add(Arc::new(Animated::new(
+        Arc::new(tall_box),
+        Keyframes::constant(tall_box_placement),
+    )?));
+
+    let short_box = quad_box(DVec3::ZERO, DVec3::new(165.0, 165.0, 165.0), white)?;
+    let short_box_placement = Transform::translation(DVec3::new(130.0, 0.0, 65.0))
+        .rotation(DQuat::from_rotation_y(-18f64.to_radians()));
+    world.add(Arc::new(Animated::new(
+        Arc::new(short_box),
+        Keyframes::constant(short_box_placement),
+    )?));
+
+    let world = BvhNode::from(world);
+
+    let mut lights = HittableList::default();
+    lights.add(ceiling_light);
+
+    let cam = Camera::init()
+        .aspect_ratio(1.0)
+        .image_width(600)
+        .samples_per_pixel(50)
+        .max_depth(50)
+        .background(Background::Solid(DVec3::ZERO))
+        .lights(Arc::new(lights))
+        .vfov(40.0)
+        .look_from(DVec3::new(278.0, 278.0, -800.0))
+        .look_at(DVec3::new(278.0, 278.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn simple_light(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Checker::from_colors(0.5, DVec3::new(0.2, 0.3, 0.1), DVec3::new(0.9, 0.9, 0.9));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(checker))),
+    )?));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, 2.0, 0.0),
+        2.0,
+        Arc::new(Lambertian::from_color(DVec3::new(0.4, 0.2, 0.1))),
+    )?));
+
+    let panel = Arc::new(Quad::new(
+        DVec3::new(3.0, 1.0, -2.0),
+        DVec3::new(2.0, 0.0, 0.0),
+        DVec3::new(0.0, 2.0, 0.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(4.0, 4.0, 4.0))),
+    )?);
+    world.add(panel.clone());
+
+    // Lantern glowing in a warm and a cool checker pattern
+    let lantern_pattern = Checker::new(
+        0.25,
+        Arc::new(SolidColor::new(DVec3::new(2.0, 1.0, 0.3))),
+        Arc::new(SolidColor::new(DVec3::new(0.3, 0.6, 2.0))),
+    );
+    let lantern = Arc::new(Sphere::stationary(
+        DVec3::new(-1.0, 5.0, -4.0),
+        1.0,
+        Arc::new(DiffuseLight::from_texture(Arc::new(lantern_pattern))),
+    )?);
+    world.add(lantern.clone());
+
+    let world = BvhNode::from(world);
+
+    let mut lights = HittableList::default();
+    lights.add(panel);
+    lights.add(lantern);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .background(Background::Solid(DVec3::ZERO))
+        .lights(Arc::new(lights))
+        .vfov(20.0)
+        .look_from(DVec3::new(26.0, 3.0, 6.0))
+        .look_at(DVec3::new(0.0, 2.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn metals_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Checker::from_colors(0.5, DVec3::new(0.2, 0.2, 0.2), DVec3::new(0.8, 0.8, 0.8));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(checker))),
+    )?));
+
+    let metals = [
+        Conductor::aluminium(0.0),
+        Conductor::gold(0.25),
+        Conductor::copper(0.35).anisotropy(0.8),
+        Conductor::silver(0.5),
+    ];
+    for (i, metal) in metals.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(-3.3 + 2.2 * i as f64, 1.0, 0.0),
+            1.0,
+            Arc::new(metal),
+        )?));
+    }
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-3.0, 6.0, -1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(3.0, 3.0, 3.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .lights(light)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn principled_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Checker::from_colors(0.5, DVec3::new(0.2, 0.2, 0.2), DVec3::new(0.8, 0.8, 0.8));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(checker))),
+    )?));
+
+    let constant = |value: f64| Arc::new(SolidColor::new(DVec3::splat(value)));
+    let materials = [
+        // Lacquered red plastic
+        Principled::from_color(DVec3::new(0.7, 0.05, 0.05))
+            .roughness(constant(0.4))
+            .clearcoat(constant(1.0)),
+        // Gold with patches of polish, roughness driven by a texture
+        Principled::from_color(DVec3::new(1.0, 0.78, 0.34))
+            .metallic(constant(1.0))
+            .roughness(Arc::new(Checker::from_colors(
+                0.15,
+                DVec3::splat(0.1),
+                DVec3::splat(0.45),
+            ))),
+        // Velvet
+        Principled::from_color(DVec3::new(0.1, 0.15, 0.5))
+            .roughness(constant(1.0))
+            .specular(constant(0.2))
+            .sheen(constant(1.0)),
+        // Green glass
+        Principled::from_color(DVec3::new(0.8, 1.0, 0.85))
+            .roughness(constant(0.0))
+            .specular_tint(constant(0.5))
+            .transmission(constant(1.0))
+            .ior(constant(1.5)),
+    ];
+    for (i, material) in materials.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(-3.3 + 2.2 * i as f64, 1.0, 0.0),
+            1.0,
+            Arc::new(material),
+        )?));
+    }
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-3.0, 6.0, -1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(3.0, 3.0, 3.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .lights(light)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+pub(crate) fn frosted_glass_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Checker::from_colors(0.5, DVec3::new(0.2, 0.3, 0.1), DVec3::new(0.9, 0.9, 0.9));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(checker))),
+    )?));
+
+    for (i, roughness) in [0.0, 0.15, 0.4].into_iter().enumerate() {
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(-3.3 + 2.2 * i as f64, 1.0, 0.0),
+            1.0,
+            Arc::new(RoughDielectric::new(1.5, roughness)),
+        )?));
+    }
+
+    // Ground glass panel in front of a red ball
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(3.3, 0.7, -1.0),
+        0.7,
+        Arc::new(Lambertian::from_color(DVec3::new(0.8, 0.1, 0.1))),
+    )?));
+    world.add(Arc::new(quad_box(
+        DVec3::new(2.4, 0.0, 0.4),
+        DVec3::new(4.2, 2.0, 0.5),
+        Arc::new(RoughDielectric::new(1.5, 0.3)),
+    )?));
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-3.0, 6.0, -1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(3.0, 3.0, 3.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .lights(light)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+/// Dispersive glass in front of a checkered wall, render with spectral mode
+/// on to see the colors split
+pub(crate) fn dispersion_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Arc::new(Checker::from_colors(
+        0.4,
+        DVec3::new(0.05, 0.05, 0.05),
+        DVec3::new(0.9, 0.9, 0.9),
+    ));
+    world.add(Arc::new(Quad::new(
+        DVec3::new(-8.0, -0.5, -3.0),
+        DVec3::new(16.0, 0.0, 0.0),
+        DVec3::new(0.0, 8.0, 0.0),
+        Arc::new(Lambertian::from_texture(checker.clone())),
+    )?));
+    world.add(Arc::new(Quad::new(
+        DVec3::new(-8.0, -0.5, 6.0),
+        DVec3::new(16.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, -9.0),
+        Arc::new(Lambertian::from_texture(checker)),
+    )?));
+
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(-2.6, 0.7, 0.0),
+        1.2,
+        Arc::new(Dielectric::dispersive(Dispersion::diamond())),
+    )?));
+    world.add(Arc::new(Animated::new(
+        Arc::new(quad_box(
+            DVec3::new(-0.8, -0.8, -0.8),
+            DVec3::new(0.8, 0.8, 0.8),
+            Arc::new(Dielectric::dispersive(Dispersion::bk7())),
+        )?),
+        Keyframes::constant(
+            Transform::translation(DVec3::new(0.0, 0.6, 0.0))
+                .rotation(DQuat::from_euler(glam::EulerRot::YXZ, 0.6, 0.5, 0.0)),
+        ),
+    )?));
+    // Far more dispersive than any real glass, to make the effect obvious
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(2.6, 0.7, 0.0),
+        1.2,
+        Arc::new(Dielectric::dispersive(Dispersion::Cauchy { a: 1.45, b: 0.06 })),
+    )?));
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-3.0, 7.0, 1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 3.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(4.0, 4.0, 4.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(200)
+        .max_depth(20)
+        .background(Background::Solid(DVec3::new(0.1, 0.1, 0.12)))
+        .lights(light)
+        .vfov(40.0)
+        .look_from(DVec3::new(0.0, 2.0, 9.0))
+        .look_at(DVec3::new(0.0, 0.6, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+/// Nested dielectrics: a glass of water, air bubbles in glass and tinted
+/// glass getting darker where it is thicker
+pub(crate) fn media_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Checker::from_colors(0.5, DVec3::new(0.2, 0.2, 0.2), DVec3::new(0.8, 0.8, 0.8));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(checker))),
+    )?));
+
+    // Overlapping slabs of the same glass merge into one hollow cup. The
+    // water overlaps the walls, which win thanks to their higher priority.
+    let glass = Arc::new(Dielectric::new(1.5).priority(2));
+    let (x, base) = (-2.4, 0.01);
+    let slabs = [
+        (DVec3::new(-0.6, 0.0, -0.6), DVec3::new(0.6, 0.2, 0.6)),
+        (DVec3::new(-0.6, 0.0, -0.6), DVec3::new(-0.5, 2.0, 0.6)),
+        (DVec3::new(0.5, 0.0, -0.6), DVec3::new(0.6, 2.0, 0.6)),
+        (DVec3::new(-0.6, 0.0, -0.6), DVec3::new(0.6, 2.0, -0.5)),
+        (DVec3::new(-0.6, 0.0, 0.5), DVec3::new(0.6, 2.0, 0.6)),
+    ];
+    let offset = DVec3::new(x, base, 0.0);
+    for (a, b) in slabs {
+        world.add(Arc::new(quad_box(a + offset, b + offset, glass.clone())?));
+    }
+    let water = Dielectric::new(1.33)
+        .tint(DVec3::new(0.7, 0.9, 1.0), 1.0)
+        .priority(1);
+    world.add(Arc::new(quad_box(
+        DVec3::new(-0.55, 0.15, -0.55) + offset,
+        DVec3::new(0.55, 1.4, 0.55) + offset,
+        Arc::new(water),
+    )?));
+
+    // Air bubbles in a glass ball
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new(1.5)),
+    )?));
+    let air = Arc::new(Dielectric::new(1.0).priority(1));
+    for (center, radius) in [
+        (DVec3::new(-0.3, 1.2, 0.3), 0.3),
+        (DVec3::new(0.35, 0.7, 0.2), 0.2),
+        (DVec3::new(0.1, 1.5, 0.5), 0.12),
+    ] {
+        world.add(Arc::new(Sphere::stationary(center, radius, air.clone())?));
+    }
+
+    // Deep red glass, lighter near the thin edges
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(2.4, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new(1.5).tint(DVec3::new(0.9, 0.2, 0.1), 1.0)),
+    )?));
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-3.0, 6.0, -1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(3.0, 3.0, 3.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(30)
+        .lights(light)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+/// Translucent materials lit from behind, with a plain diffuse ball on the
+/// left to compare
+pub(crate) fn subsurface_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Checker::from_colors(0.5, DVec3::new(0.2, 0.2, 0.2), DVec3::new(0.8, 0.8, 0.8));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(checker))),
+    )?));
+
+    let skin = DVec3::new(0.85, 0.55, 0.45);
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(-3.3, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::from_color(skin)),
+    )?));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(-1.1, 1.0, 0.0),
+        1.0,
+        Arc::new(Subsurface::from_albedo(
+            skin,
+            DVec3::new(0.5, 0.2, 0.1),
+            1.4,
+        )),
+    )?));
+    // Jade, from measured looking coefficients rather than a color
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(1.1, 1.0, 0.0),
+        1.0,
+        Arc::new(Subsurface::from_coefficients(
+            DVec3::new(6.0, 8.0, 6.0),
+            DVec3::new(1.5, 0.2, 1.0),
+            1.6,
+        )),
+    )?));
+    // Block of wax, any closed object works
+    world.add(Arc::new(quad_box(
+        DVec3::new(2.5, 0.0, -0.8),
+        DVec3::new(4.1, 2.2, 0.8),
+        Arc::new(Subsurface::from_albedo(
+            DVec3::new(0.9, 0.85, 0.7),
+            DVec3::splat(0.3),
+            1.45,
+        )),
+    )?));
+
+    let mut lights = HittableList::default();
+    let key = Arc::new(Quad::new(
+        DVec3::new(-3.0, 6.0, -1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(2.0, 2.0, 2.0))),
+    )?);
+    let back = Arc::new(Quad::new(
+        DVec3::new(-5.0, 0.5, -4.0),
+        DVec3::new(10.0, 0.0, 0.0),
+        DVec3::new(0.0, 3.0, 0.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(4.0, 4.0, 4.0))),
+    )?);
+    world.add(key.clone());
+    world.add(back.clone());
+    lights.add(key);
+    lights.add(back);
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(200)
+        .max_depth(30)
+        .background(Background::Solid(DVec3::new(0.05, 0.05, 0.07)))
+        .lights(Arc::new(lights))
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
+
+/// Mixed and coated materials
+pub(crate) fn layered_scene(config: &RenderConfig) -> Result<()> {
+    let mut world = HittableList::default();
+
+    let checker = Checker::from_colors(0.5, DVec3::new(0.2, 0.2, 0.2), DVec3::new(0.8, 0.8, 0.8));
+    world.add(Arc::new(Sphere::stationary(
+        DVec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Arc::new(checker))),
+    )?));
+
+    let rust = Arc::new(Lambertian::from_color(DVec3::new(0.45, 0.18, 0.06)));
+    let patches = Arc::new(Checker::from_colors(0.25, DVec3::ZERO, DVec3::ONE));
+    let materials: [Arc<dyn Material>; 4] = [
+        // Rust patches on steel
+        Arc::new(MixMaterial::new(
+            Arc::new(Conductor::silver(0.3)),
+            rust,
+            patches,
+        )),
+        // Car paint
+        Arc::new(Coated::new(
+            Arc::new(Lambertian::from_color(DVec3::new(0.6, 0.02, 0.02))),
+            1.5,
+        )),
+        // Amber varnish over brushed copper
+        Arc::new(
+            Coated::new(Arc::new(Conductor::copper(0.4).anisotropy(0.7)), 1.5)
+                .tint(DVec3::new(0.9, 0.6, 0.3)),
+        ),
+        // Lacquer over speckled blue and gold
+        Arc::new(Coated::new(
+            Arc::new(MixMaterial::uniform(
+                Arc::new(Lambertian::from_color(DVec3::new(0.05, 0.1, 0.4))),
+                Arc::new(Conductor::gold(0.2)),
+                0.3,
+            )),
+            1.5,
+        )),
+    ];
+    for (i, material) in materials.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::stationary(
+            DVec3::new(-3.3 + 2.2 * i as f64, 1.0, 0.0),
+            1.0,
+            material,
+        )?));
+    }
+
+    let light = Arc::new(Quad::new(
+        DVec3::new(-3.0, 6.0, -1.0),
+        DVec3::new(6.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::from_color(DVec3::new(3.0, 3.0, 3.0))),
+    )?);
+    world.add(light.clone());
+
+    let world = BvhNode::from(world);
+
+    let cam = Camera::init()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(20)
+        .lights(light)
+        .vfov(35.0)
+        .look_from(DVec3::new(0.0, 3.0, 12.0))
+        .look_at(DVec3::new(0.0, 1.0, 0.0))
+        .vup(DVec3::new(0.0, 1.0, 0.0))
+        .build();
+
+    cam.render(&world, config)
+}
@@ -0,0 +1,169 @@
+use glam::{DMat3, DVec3};
+use std::sync::LazyLock;
+
+/// Visible range sampled in spectral mode, in nanometers
+pub const LAMBDA_MIN: f64 = 380.0;
+pub const LAMBDA_MAX: f64 = 780.0;
+
+/// Wavelength the single refraction index of a dispersive material is
+/// quoted at, the sodium d-line
+pub const LAMBDA_D: f64 = 587.6;
+
+/// Linear sRGB of a flat unit spectrum. Dividing by it white balances the
+/// film, so a flat spectrum comes out as RGB 1, 1, 1 like in RGB mode.
+static WHITE: LazyLock<DVec3> = LazyLock::new(|| {
+    let steps = (LAMBDA_MAX - LAMBDA_MIN) as usize;
+    let xyz = (0..steps)
+        .map(|i| cie_xyz(LAMBDA_MIN + i as f64 + 0.5))
+        .sum::<DVec3>();
+    xyz_to_srgb(xyz)
+});
+
+/// Wavelengths carried by a path in spectral mode.
+///
+/// The radiance of such a path holds one value per wavelength in its `x`,
+/// `y` and `z` components, in place of red, green and blue. The first
+/// wavelength is the hero: when a material sends each wavelength in a
+/// different direction, the path follows the hero and drops the others.
+#[derive(Debug, Clone, Copy)]
+pub struct SampledWavelengths {
+    pub lambda: DVec3,
+    /// Only the hero wavelength is still carried
+    hero_only: bool,
+}
+
+impl SampledWavelengths {
+    /// Three wavelengths spread evenly over the visible range, starting from
+    /// the hero at `u` in [0, 1)
+    pub fn sample(u: f64) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let wrap = |offset: f64| LAMBDA_MIN + ((u + offset) % 1.0) * range;
+        Self {
+            lambda: DVec3::new(wrap(0.0), wrap(1.0 / 3.0), wrap(2.0 / 3.0)),
+            hero_only: false,
+        }
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.lambda.x
+    }
+
+    /// Same wavelengths with all but the hero dropped
+    pub fn hero_only(self) -> Self {
+        Self {
+            hero_only: true,
+            ..self
+        }
+    }
+
+    /// Throughput weight for going from `self` to `next`. Dropping the
+    /// secondary wavelengths zeroes them and triples the hero, which is
+    /// now the only sample of the spectrum.
+    pub fn termination_weight(&self, next: &Self) -> DVec3 {
+        if next.hero_only && !self.hero_only {
+            DVec3::new(3.0, 0.0, 0.0)
+        } else {
+            DVec3::ONE
+        }
+    }
+
+    /// Spectrum of the linear sRGB reflectance or radiance `rgb`, at these
+    /// wavelengths.
+    ///
+    /// The spectrum blends smooth blue, green and red bands that sum to one
+    /// everywhere, so white stays flat and reflectances in [0, 1] stay in
+    /// [0, 1]. Saturated colors come out slightly paler than their RGB.
+    pub fn upsample(&self, rgb: DVec3) -> DVec3 {
+        let at = |lambda: f64| {
+            let blue_to_green = smoothstep((lambda - 490.0) / 20.0);
+            let green_to_red = smoothstep((lambda - 585.0) / 20.0);
+            rgb.x * green_to_red
+                + rgb.y * (blue_to_green - green_to_red)
+                + rgb.z * (1.0 - blue_to_green)
+        };
+        DVec3::new(at(self.lambda.x), at(self.lambda.y), at(self.lambda.z))
+    }
+
+    /// Linear sRGB estimate of the spectrum sampled as `radiance` at these
+    /// wavelengths. Radiance gathered after the secondary wavelengths were
+    /// dropped only has a hero value, already tripled by
+    /// [`SampledWavelengths::termination_weight`], so all three are always
+    /// averaged.
+    pub fn to_rgb(self, radiance: DVec3) -> DVec3 {
+        let pdf = 1.0 / (LAMBDA_MAX - LAMBDA_MIN);
+        let xyz = (radiance.x * cie_xyz(self.lambda.x)
+            + radiance.y * cie_xyz(self.lambda.y)
+            + radiance.z * cie_xyz(self.lambda.z))
+            / (3.0 * pdf);
+        xyz_to_srgb(xyz) / *WHITE
+    }
+}
+
+/// Smooth 0 to 1 transition over [-1, 1]
+fn smoothstep(x: f64) -> f64 {
+    let t = (0.5 * (x + 1.0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// CIE 1931 color matching functions, using the multi lobe fit from Wyman,
+/// Sloan and Shirley 2013 "Simple Analytic Approximations to the CIE XYZ
+/// Color Matching Functions"
+fn cie_xyz(lambda: f64) -> DVec3 {
+    let g = |mu: f64, sigma_low: f64, sigma_high: f64| {
+        let sigma = if lambda < mu { sigma_low } else { sigma_high };
+        let t = (lambda - mu) / sigma;
+        (-0.5 * t * t).exp()
+    };
+    DVec3::new(
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    )
+}
+
+pub(crate) fn xyz_to_srgb(xyz: DVec3) -> DVec3 {
+    let m = DMat3::from_cols(
+        DVec3::new(3.2406, -0.9689, 0.0557),
+        DVec3::new(-1.5372, 1.8758, -0.2040),
+        DVec3::new(-0.4986, 0.0415, 1.0570),
+    );
+    m * xyz
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mean film color of a flat spectrum over evenly spread heroes, with
+    /// the secondary wavelengths kept or dropped before any light arrives
+    fn mean_rgb(drop_secondary: bool) -> DVec3 {
+        let steps = 4000;
+        (0..steps)
+            .map(|i| {
+                let wavelengths = SampledWavelengths::sample((i as f64 + 0.5) / steps as f64);
+                if drop_secondary {
+                    let hero = wavelengths.hero_only();
+                    hero.to_rgb(wavelengths.termination_weight(&hero) * DVec3::ONE)
+                } else {
+                    wavelengths.to_rgb(DVec3::ONE)
+                }
+            })
+            .sum::<DVec3>()
+            / steps as f64
+    }
+
+    #[test]
+    fn flat_spectrum_keeps_its_color_when_dropping_secondary_wavelengths() {
+        let all = mean_rgb(false);
+        let hero = mean_rgb(true);
+        assert!(all.abs_diff_eq(DVec3::ONE, 1e-2), "{all}");
+        assert!(hero.abs_diff_eq(all, 1e-2), "{hero} != {all}");
+    }
+
+    #[test]
+    fn light_gathered_before_dropping_secondary_wavelengths_is_kept() {
+        let wavelengths = SampledWavelengths::sample(0.2);
+        let before = wavelengths.to_rgb(DVec3::ONE);
+        assert_eq!(wavelengths.hero_only().to_rgb(DVec3::ONE), before);
+    }
+}
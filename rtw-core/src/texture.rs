@@ -1,8 +1,46 @@
 pub mod checker;
+pub mod image;
+pub mod node;
+pub mod noise;
+pub mod pattern;
+pub mod ramp;
 pub mod solid;
+pub mod transform;
+pub mod triplanar;
+pub mod worley;
 
 use glam::DVec3;
 
+use crate::hittable::HitRecord;
+
+/// Area of a surface seen by one camera sample, as the change of the hit
+/// point and its (u, v) coordinates towards the neighbouring pixels
+#[derive(Debug, Clone, Copy)]
+pub struct Footprint {
+    pub dpdx: DVec3,
+    pub dpdy: DVec3,
+    pub dudx: f64,
+    pub dudy: f64,
+    pub dvdx: f64,
+    pub dvdy: f64,
+}
+
 pub trait Texture: Send + Sync {
     fn value(&self, u: f64, v: f64, point: DVec3) -> DVec3;
+
+    /// Average value over `footprint` around the given point, so detail
+    /// smaller than a pixel blends instead of aliasing. Textures without a
+    /// way to filter themselves return the value at the center.
+    fn filtered(&self, u: f64, v: f64, point: DVec3, _footprint: &Footprint) -> DVec3 {
+        self.value(u, v, point)
+    }
+
+    /// Value at a hit, filtered when the ray that found it tracked its
+    /// footprint
+    fn value_at(&self, hr: &HitRecord) -> DVec3 {
+        match &hr.footprint {
+            Some(footprint) => self.filtered(hr.u, hr.v, hr.point, footprint),
+            None => self.value(hr.u, hr.v, hr.point),
+        }
+    }
 }
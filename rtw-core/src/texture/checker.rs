@@ -1,8 +1,53 @@
 use glam::DVec3;
 
-use crate::texture::{Texture, solid::SolidColor};
+use crate::texture::{Footprint, Texture, solid::SolidColor};
 use std::sync::Arc;
 
+/// Share of odd cells along one axis within `half_width` cells of `x`, in
+/// closed form from the integral of a square wave
+fn odd_share(x: f64, half_width: f64) -> f64 {
+    if half_width <= 0.0 {
+        return f64::from(u8::from(x.floor().rem_euclid(2.0) == 1.0));
+    }
+    // Length of the odd cells between 0 and `x`
+    let odd_length = |x: f64| {
+        let periods = (x / 2.0).floor();
+        periods + (x - 2.0 * periods - 1.0).max(0.0)
+    };
+    (odd_length(x + half_width) - odd_length(x - half_width)) / (2.0 * half_width)
+}
+
+/// Share of cells with an odd sum of indices, given the odd share along each
+/// axis, assuming the axes vary independently over the footprint
+fn odd_parity(shares: impl IntoIterator<Item = f64>) -> f64 {
+    shares
+        .into_iter()
+        .fold(0.0, |odd, share| odd + share - 2.0 * odd * share)
+}
+
+/// Blend of the two textures of a checkerboard by the share of odd cells
+fn blend(
+    even: &dyn Texture,
+    odd: &dyn Texture,
+    odd_share: f64,
+    (u, v, point): (f64, f64, DVec3),
+    footprint: &Footprint,
+) -> DVec3 {
+    let mut color = DVec3::ZERO;
+    if odd_share < 1.0 {
+        color += (1.0 - odd_share) * even.filtered(u, v, point, footprint);
+    }
+    if odd_share > 0.0 {
+        color += odd_share * odd.filtered(u, v, point, footprint);
+    }
+    color
+}
+
+/// Checkerboard in 3D space, cells of `scale` on each side. Tiles stay put
+/// in the world as objects move through them.
+///
+/// Filtered, it blends the two textures by how much of each the footprint
+/// covers, measured over a box around it.
 pub struct Checker {
     inv_scale: f64,
     even: Arc<dyn Texture>,
@@ -38,4 +83,60 @@ impl Texture for Checker {
             self.odd.value(u, v, point)
         }
     }
+
+    fn filtered(&self, u: f64, v: f64, point: DVec3, footprint: &Footprint) -> DVec3 {
+        let half_width = footprint.dpdx.abs().max(footprint.dpdy.abs()) * self.inv_scale;
+        let center = point * self.inv_scale;
+        let share = odd_parity((0..3).map(|axis| odd_share(center[axis], half_width[axis])));
+        blend(&*self.even, &*self.odd, share, (u, v, point), footprint)
+    }
+}
+
+/// Checkerboard over the (u, v) coordinates of a surface, `scale` being the
+/// size of a tile in (u, v), so it sticks to the surface and follows it
+pub struct UvChecker {
+    inv_scale: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl UvChecker {
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        Self {
+            inv_scale: scale.recip(),
+            even,
+            odd,
+        }
+    }
+
+    pub fn from_colors(scale: f64, color1: DVec3, color2: DVec3) -> Self {
+        Self::new(
+            scale,
+            Arc::new(SolidColor::new(color1)),
+            Arc::new(SolidColor::new(color2)),
+        )
+    }
+}
+
+impl Texture for UvChecker {
+    fn value(&self, u: f64, v: f64, point: DVec3) -> DVec3 {
+        let u_int = (self.inv_scale * u).floor() as i64;
+        let v_int = (self.inv_scale * v).floor() as i64;
+
+        if (u_int + v_int).rem_euclid(2) == 0 {
+            self.even.value(u, v, point)
+        } else {
+            self.odd.value(u, v, point)
+        }
+    }
+
+    fn filtered(&self, u: f64, v: f64, point: DVec3, footprint: &Footprint) -> DVec3 {
+        let u_width = footprint.dudx.abs().max(footprint.dudy.abs());
+        let v_width = footprint.dvdx.abs().max(footprint.dvdy.abs());
+        let share = odd_parity([
+            odd_share(u * self.inv_scale, u_width * self.inv_scale),
+            odd_share(v * self.inv_scale, v_width * self.inv_scale),
+        ]);
+        blend(&*self.even, &*self.odd, share, (u, v, point), footprint)
+    }
 }
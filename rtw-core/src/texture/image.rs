@@ -0,0 +1,277 @@
+use glam::{DVec2, DVec3};
+
+use crate::{
+    Result, RtwError,
+    texture::{Footprint, Texture},
+    utils::gamma::gamma_to_linear,
+};
+
+/// Longest the footprint may get against its width before EWA filtering
+/// widens it, bounding the texels read for a lookup
+const MAX_ANISOTROPY: f64 = 8.0;
+
+/// Falloff of the Gaussian used by EWA filtering
+const EWA_ALPHA: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Filter {
+    /// Use the texel the (u, v) coordinate falls into
+    Nearest,
+    /// Blend the four texels surrounding the (u, v) coordinate
+    Bilinear,
+    /// Blend bilinear lookups in the two mipmap levels with texels closest
+    /// in size to a square around the footprint. Cheap, but blurs surfaces
+    /// seen at grazing angles.
+    Trilinear,
+    /// Weigh the texels under the footprint ellipse with a Gaussian, as in
+    /// Heckbert 1989 "Fundamentals of Texture Mapping and Image Warping",
+    /// keeping detail along the short axis of stretched footprints
+    Ewa,
+}
+
+/// Texture sampled from a PNG, JPEG or PPM image by (u, v) coordinates.
+///
+/// `u` runs left to right and `v` bottom to top. Coordinates outside of
+/// [0, 1] are clamped to the image edges, unless the image repeats.
+///
+/// Lookups through a ray footprint read from a mipmap pyramid of the image
+/// for the filters that use one, and are unfiltered otherwise.
+pub struct ImageTexture {
+    /// The image followed by halved copies of it down to a single texel,
+    /// only the image for filters without mipmapping
+    levels: Vec<Level>,
+    filter: Filter,
+}
+
+/// One image of a mipmap pyramid
+struct Level {
+    width: usize,
+    height: usize,
+    /// Texels in linear space or raw data, row major starting at the top left
+    texels: Vec<DVec3>,
+    /// Wrap lookups past the edges around to the other side
+    repeat: bool,
+}
+
+impl Level {
+    /// Texel at the given column and row, clamped to the edges or wrapped
+    /// around
+    fn texel(&self, x: isize, y: isize) -> DVec3 {
+        let (width, height) = (self.width as isize, self.height as isize);
+        let (x, y) = if self.repeat {
+            (x.rem_euclid(width), y.rem_euclid(height))
+        } else {
+            (x.clamp(0, width - 1), y.clamp(0, height - 1))
+        };
+        self.texels[y as usize * self.width + x as usize]
+    }
+
+    /// Texel covering the image coordinates (s, t) in [0, 1], `t` going down
+    fn nearest(&self, st: DVec2) -> DVec3 {
+        let x = (st.x * self.width as f64).floor() as isize;
+        let y = (st.y * self.height as f64).floor() as isize;
+        self.texel(x, y)
+    }
+
+    fn bilinear(&self, st: DVec2) -> DVec3 {
+        // Texel centers sit at half integer coordinates
+        let x = st.x * self.width as f64 - 0.5;
+        let y = st.y * self.height as f64 - 0.5;
+        let (x0, y0) = (x.floor() as isize, y.floor() as isize);
+        let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+
+        let top = self.texel(x0, y0).lerp(self.texel(x0 + 1, y0), tx);
+        let bottom = self.texel(x0, y0 + 1).lerp(self.texel(x0 + 1, y0 + 1), tx);
+        top.lerp(bottom, ty)
+    }
+
+    /// Level of half the size, each texel averaging the 2x2 block below it
+    fn downsample(&self) -> Self {
+        let (width, height) = (self.width.div_ceil(2), self.height.div_ceil(2));
+        let texels = (0..height as isize)
+            .flat_map(|y| (0..width as isize).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let (x, y) = (2 * x, 2 * y);
+                (self.texel(x, y)
+                    + self.texel(x + 1, y)
+                    + self.texel(x, y + 1)
+                    + self.texel(x + 1, y + 1))
+                    / 4.0
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            texels,
+            repeat: self.repeat,
+        }
+    }
+
+    /// Gaussian weighted average of the texels within the ellipse around
+    /// `st` with the given axes, in image coordinates
+    fn ewa(&self, st: DVec2, axis0: DVec2, axis1: DVec2) -> DVec3 {
+        let size = DVec2::new(self.width as f64, self.height as f64);
+        let center = st * size - 0.5;
+        let (a0, a1) = (axis0 * size, axis1 * size);
+
+        // Implicit ellipse A x^2 + B x y + C y^2 < 1, grown by a texel so
+        // it never falls between texel centers
+        let a = a0.y * a0.y + a1.y * a1.y + 1.0;
+        let b = -2.0 * (a0.x * a0.y + a1.x * a1.y);
+        let c = a0.x * a0.x + a1.x * a1.x + 1.0;
+        let inv_f = (a * c - 0.25 * b * b).recip();
+        let (a, b, c) = (a * inv_f, b * inv_f, c * inv_f);
+
+        // Bounding box of the ellipse, never wider than the level itself
+        let det = 4.0 * a * c - b * b;
+        let reach = (2.0 * DVec2::new(c.sqrt(), a.sqrt()) / det.sqrt()).min(size);
+        let (min, max) = ((center - reach).ceil(), (center + reach).floor());
+
+        let mut sum = DVec3::ZERO;
+        let mut weights = 0.0;
+        for y in min.y as isize..=max.y as isize {
+            for x in min.x as isize..=max.x as isize {
+                let d = DVec2::new(x as f64, y as f64) - center;
+                let r2 = a * d.x * d.x + b * d.x * d.y + c * d.y * d.y;
+                if r2 < 1.0 {
+                    let weight = (-EWA_ALPHA * r2).exp() - (-EWA_ALPHA).exp();
+                    sum += weight * self.texel(x, y);
+                    weights += weight;
+                }
+            }
+        }
+        if weights > 0.0 {
+            sum / weights
+        } else {
+            self.bilinear(st)
+        }
+    }
+}
+
+impl ImageTexture {
+    pub fn open(path: &str, filter: Filter) -> Result<Self> {
+        Self::load(path, filter, |p| {
+            DVec3::new(
+                gamma_to_linear(channel(p[0])),
+                gamma_to_linear(channel(p[1])),
+                gamma_to_linear(channel(p[2])),
+            )
+        })
+    }
+
+    /// Image holding data rather than colors, such as a normal or bump map,
+    /// whose values are used as stored without linearizing them
+    pub fn open_raw(path: &str, filter: Filter) -> Result<Self> {
+        Self::load(path, filter, |p| {
+            DVec3::new(channel(p[0]), channel(p[1]), channel(p[2]))
+        })
+    }
+
+    /// Alpha channel of the image in all three channels, for cutout masks.
+    /// Images without an alpha channel are fully opaque.
+    pub fn open_alpha(path: &str, filter: Filter) -> Result<Self> {
+        Self::load(path, filter, |p| DVec3::splat(channel(p[3])))
+    }
+
+    fn load(path: &str, filter: Filter, decode: fn([u8; 4]) -> DVec3) -> Result<Self> {
+        let img = image::open(path)
+            .map_err(|err| RtwError::ImageError(err.to_string()))?
+            .into_rgba8();
+        if img.width() == 0 || img.height() == 0 {
+            return Err(RtwError::ImageError(format!("{path} has no pixels")));
+        }
+
+        let mut levels = vec![Level {
+            width: img.width() as usize,
+            height: img.height() as usize,
+            texels: img.pixels().map(|p| decode(p.0)).collect(),
+            repeat: false,
+        }];
+        if matches!(filter, Filter::Trilinear | Filter::Ewa) {
+            while let Some(last) = levels.last()
+                && (last.width > 1 || last.height > 1)
+            {
+                levels.push(last.downsample());
+            }
+        }
+
+        Ok(Self { levels, filter })
+    }
+
+    /// Tiles the image over coordinates outside of [0, 1] instead of
+    /// stretching its edges
+    pub fn repeat(mut self) -> Self {
+        for level in &mut self.levels {
+            level.repeat = true;
+        }
+        self
+    }
+
+    /// Blend of `lookup` in the two levels around the fractional level
+    /// `lod`, 0 being the full image
+    fn between_levels(&self, lod: f64, lookup: impl Fn(&Level) -> DVec3) -> DVec3 {
+        let lod = lod.clamp(0.0, (self.levels.len() - 1) as f64);
+        let below = lod.floor() as usize;
+        let fine = lookup(&self.levels[below]);
+        match self.levels.get(below + 1) {
+            Some(coarse) if lod > below as f64 => fine.lerp(lookup(coarse), lod - below as f64),
+            _ => fine,
+        }
+    }
+}
+
+fn channel(value: u8) -> f64 {
+    value as f64 / 255.0
+}
+
+/// Image coordinates of (u, v), rows going top to bottom while v goes
+/// bottom to top
+fn image_coordinates(u: f64, v: f64) -> DVec2 {
+    DVec2::new(u, 1.0 - v)
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _point: DVec3) -> DVec3 {
+        let st = image_coordinates(u, v);
+        match self.filter {
+            Filter::Nearest => self.levels[0].nearest(st),
+            Filter::Bilinear | Filter::Trilinear | Filter::Ewa => self.levels[0].bilinear(st),
+        }
+    }
+
+    fn filtered(&self, u: f64, v: f64, point: DVec3, footprint: &Footprint) -> DVec3 {
+        let st = image_coordinates(u, v);
+        // Footprint axes in texels of the full image
+        let size = DVec2::new(self.levels[0].width as f64, self.levels[0].height as f64);
+        let mut major = DVec2::new(footprint.dudx, -footprint.dvdx) * size;
+        let mut minor = DVec2::new(footprint.dudy, -footprint.dvdy) * size;
+        match self.filter {
+            Filter::Nearest | Filter::Bilinear => self.value(u, v, point),
+            Filter::Trilinear => {
+                let width = 2.0 * major.abs().max(minor.abs()).max_element();
+                self.between_levels(width.log2(), |level| level.bilinear(st))
+            }
+            Filter::Ewa => {
+                if major.length_squared() < minor.length_squared() {
+                    std::mem::swap(&mut major, &mut minor);
+                }
+                let (major_length, minor_length) = (major.length(), minor.length());
+                if minor_length == 0.0 {
+                    return self.levels[0].bilinear(st);
+                }
+                // Widen very long footprints rather than reading too many
+                // texels, trading some blur for speed
+                if minor_length * MAX_ANISOTROPY < major_length {
+                    minor *= major_length / (minor_length * MAX_ANISOTROPY);
+                }
+                let lod = minor.length().log2();
+                // Footprints as wide as the image only see its average color
+                let top = self.levels.len() - 1;
+                if lod >= top as f64 {
+                    return self.levels[top].texel(0, 0);
+                }
+                self.between_levels(lod, |level| level.ewa(st, major / size, minor / size))
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use glam::DVec3;
+use std::sync::Arc;
+
+use crate::{
+    hittable::HitRecord,
+    texture::{Footprint, Texture, ramp::ColorRamp},
+    utils::interval::Interval,
+};
+
+/// Texture computed from other textures, all looked up in the same way as
+/// the node itself. Nodes pass filtering and hits down to their inputs, so
+/// filtered inputs and triplanar projection keep working inside a graph.
+///
+/// Scalar inputs such as masks are read from their first channel, like the
+/// scalar textures of materials.
+trait Node: Send + Sync {
+    fn combine(&self, lookup: &dyn Fn(&dyn Texture) -> DVec3) -> DVec3;
+}
+
+impl<T: Node> Texture for T {
+    fn value(&self, u: f64, v: f64, point: DVec3) -> DVec3 {
+        self.combine(&|input| input.value(u, v, point))
+    }
+
+    fn filtered(&self, u: f64, v: f64, point: DVec3, footprint: &Footprint) -> DVec3 {
+        self.combine(&|input| input.filtered(u, v, point, footprint))
+    }
+
+    fn value_at(&self, hr: &HitRecord) -> DVec3 {
+        self.combine(&|input| input.value_at(hr))
+    }
+}
+
+/// Sum of two textures
+pub struct Add {
+    a: Arc<dyn Texture>,
+    b: Arc<dyn Texture>,
+}
+
+impl Add {
+    pub fn new(a: Arc<dyn Texture>, b: Arc<dyn Texture>) -> Self {
+        Self { a, b }
+    }
+}
+
+impl Node for Add {
+    fn combine(&self, lookup: &dyn Fn(&dyn Texture) -> DVec3) -> DVec3 {
+        lookup(&*self.a) + lookup(&*self.b)
+    }
+}
+
+/// Product of two textures per channel, such as a color darkened by dirt
+pub struct Multiply {
+    a: Arc<dyn Texture>,
+    b: Arc<dyn Texture>,
+}
+
+impl Multiply {
+    pub fn new(a: Arc<dyn Texture>, b: Arc<dyn Texture>) -> Self {
+        Self { a, b }
+    }
+}
+
+impl Node for Multiply {
+    fn combine(&self, lookup: &dyn Fn(&dyn Texture) -> DVec3) -> DVec3 {
+        lookup(&*self.a) * lookup(&*self.b)
+    }
+}
+
+/// Blend from `a` where `mask` is 0 to `b` where it is 1
+pub struct Mix {
+    a: Arc<dyn Texture>,
+    b: Arc<dyn Texture>,
+    mask: Arc<dyn Texture>,
+}
+
+impl Mix {
+    pub fn new(a: Arc<dyn Texture>, b: Arc<dyn Texture>, mask: Arc<dyn Texture>) -> Self {
+        Self { a, b, mask }
+    }
+}
+
+impl Node for Mix {
+    fn combine(&self, lookup: &dyn Fn(&dyn Texture) -> DVec3) -> DVec3 {
+        let t = lookup(&*self.mask).x.clamp(0.0, 1.0);
+        // Inputs masked out entirely aren't looked up
+        match t {
+            0.0 => lookup(&*self.a),
+            1.0 => lookup(&*self.b),
+            t => lookup(&*self.a).lerp(lookup(&*self.b), t),
+        }
+    }
+}
+
+/// Colors of a ramp picked by a scalar input, to color noise or a mask
+pub struct Ramp {
+    input: Arc<dyn Texture>,
+    ramp: ColorRamp,
+}
+
+impl Ramp {
+    pub fn new(input: Arc<dyn Texture>, ramp: ColorRamp) -> Self {
+        Self { input, ramp }
+    }
+}
+
+impl Node for Ramp {
+    fn combine(&self, lookup: &dyn Fn(&dyn Texture) -> DVec3) -> DVec3 {
+        self.ramp.sample(lookup(&*self.input).x)
+    }
+}
+
+/// One minus the input, turning masks inside out
+pub struct Invert {
+    input: Arc<dyn Texture>,
+}
+
+impl Invert {
+    pub fn new(input: Arc<dyn Texture>) -> Self {
+        Self { input }
+    }
+}
+
+impl Node for Invert {
+    fn combine(&self, lookup: &dyn Fn(&dyn Texture) -> DVec3) -> DVec3 {
+        DVec3::ONE - lookup(&*self.input)
+    }
+}
+
+/// Input stretched linearly from one range of values to another, per
+/// channel. Values outside of `from` extend past `to` unless clamped.
+pub struct Remap {
+    input: Arc<dyn Texture>,
+    from: Interval,
+    to: Interval,
+    clamp: bool,
+}
+
+impl Remap {
+    pub fn new(input: Arc<dyn Texture>, from: Interval, to: Interval) -> Self {
+        Self {
+            input,
+            from,
+            to,
+            clamp: false,
+        }
+    }
+
+    pub fn clamp(mut self) -> Self {
+        self.clamp = true;
+        self
+    }
+}
+
+impl Node for Remap {
+    fn combine(&self, lookup: &dyn Fn(&dyn Texture) -> DVec3) -> DVec3 {
+        let t = (lookup(&*self.input) - self.from.start) / (self.from.end - self.from.start);
+        let t = if self.clamp {
+            t.clamp(DVec3::ZERO, DVec3::ONE)
+        } else {
+            t
+        };
+        self.to.start + t * (self.to.end - self.to.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::solid::SolidColor;
+
+    fn constant(value: DVec3) -> Arc<dyn Texture> {
+        Arc::new(SolidColor::new(value))
+    }
+
+    fn lookup(texture: &dyn Texture) -> DVec3 {
+        texture.value(0.3, 0.7, DVec3::ZERO)
+    }
+
+    #[test]
+    fn mix_blends_by_the_clamped_mask() {
+        let (a, b) = (DVec3::new(1.0, 0.0, 0.0), DVec3::new(0.0, 0.0, 1.0));
+        let mix = |mask: f64| {
+            lookup(&Mix::new(
+                constant(a),
+                constant(b),
+                constant(DVec3::splat(mask)),
+            ))
+        };
+        assert_eq!(mix(0.0), a);
+        assert_eq!(mix(1.0), b);
+        assert_eq!(mix(0.25), DVec3::new(0.75, 0.0, 0.25));
+        assert_eq!(mix(-2.0), a);
+        assert_eq!(mix(3.0), b);
+    }
+
+    #[test]
+    fn remap_stretches_each_channel_between_ranges() {
+        let input = constant(DVec3::new(0.25, 0.5, 1.5));
+        let remap = Remap::new(input.clone(), 0.0..1.0, 2.0..4.0);
+        assert_eq!(lookup(&remap), DVec3::new(2.5, 3.0, 5.0));
+        let clamped = Remap::new(input, 0.0..1.0, 2.0..4.0).clamp();
+        assert_eq!(lookup(&clamped), DVec3::new(2.5, 3.0, 4.0));
+
+        // Reversed ranges flip the values
+        let flipped = Remap::new(constant(DVec3::splat(0.25)), 0.0..1.0, 1.0..0.0);
+        assert_eq!(lookup(&flipped), DVec3::splat(0.75));
+    }
+
+    #[test]
+    fn nodes_compose_into_graphs() {
+        let mask = Arc::new(Invert::new(constant(DVec3::splat(0.75))));
+        let graph = Add::new(
+            Arc::new(Multiply::new(mask, constant(DVec3::new(2.0, 4.0, 8.0)))),
+            Arc::new(Ramp::new(
+                constant(DVec3::splat(0.5)),
+                ColorRamp::between(DVec3::ZERO, DVec3::ONE),
+            )),
+        );
+        assert_eq!(lookup(&graph), DVec3::new(1.0, 1.5, 2.5));
+    }
+}
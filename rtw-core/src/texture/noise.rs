@@ -0,0 +1,208 @@
+use glam::DVec3;
+
+use crate::{
+    texture::{Texture, ramp::ColorRamp},
+    utils::perlin::Perlin,
+};
+
+/// Layers of noise summed by turbulence unless set otherwise
+const DEFAULT_OCTAVES: u32 = 7;
+
+/// Smooth Perlin noise, `scale` setting how many features fit in a unit of
+/// distance
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: f64,
+    ramp: ColorRamp,
+}
+
+impl NoiseTexture {
+    pub fn new(scale: f64) -> Self {
+        Self {
+            noise: Perlin::default(),
+            scale,
+            ramp: ColorRamp::between(DVec3::ZERO, DVec3::ONE),
+        }
+    }
+
+    pub fn ramp(mut self, ramp: ColorRamp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, point: DVec3) -> DVec3 {
+        self.ramp
+            .sample(0.5 * (1.0 + self.noise.noise(self.scale * point)))
+    }
+}
+
+/// Several octaves of noise magnitude, giving a rough cloudy pattern
+pub struct Turbulence {
+    noise: Perlin,
+    scale: f64,
+    octaves: u32,
+    ramp: ColorRamp,
+}
+
+impl Turbulence {
+    pub fn new(scale: f64) -> Self {
+        Self {
+            noise: Perlin::default(),
+            scale,
+            octaves: DEFAULT_OCTAVES,
+            ramp: ColorRamp::between(DVec3::ZERO, DVec3::ONE),
+        }
+    }
+
+    pub fn octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    pub fn ramp(mut self, ramp: ColorRamp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+}
+
+impl Texture for Turbulence {
+    fn value(&self, _u: f64, _v: f64, point: DVec3) -> DVec3 {
+        let turbulence = self.noise.turbulence(self.scale * point, self.octaves);
+        self.ramp.sample(turbulence.min(1.0))
+    }
+}
+
+/// Veins running across the z axis, phase shifted by turbulence
+pub struct Marble {
+    noise: Perlin,
+    scale: f64,
+    octaves: u32,
+    /// How far turbulence bends the veins
+    distortion: f64,
+    ramp: ColorRamp,
+}
+
+impl Marble {
+    pub fn new(scale: f64) -> Self {
+        Self {
+            noise: Perlin::default(),
+            scale,
+            octaves: DEFAULT_OCTAVES,
+            distortion: 10.0,
+            ramp: ColorRamp::between(DVec3::splat(0.1), DVec3::splat(0.95)),
+        }
+    }
+
+    pub fn octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    pub fn distortion(mut self, distortion: f64) -> Self {
+        self.distortion = distortion;
+        self
+    }
+
+    pub fn ramp(mut self, ramp: ColorRamp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+}
+
+impl Texture for Marble {
+    fn value(&self, _u: f64, _v: f64, point: DVec3) -> DVec3 {
+        let turbulence = self.noise.turbulence(point, self.octaves);
+        let phase = self.scale * point.z + self.distortion * turbulence;
+        self.ramp.sample(0.5 * (1.0 + phase.sin()))
+    }
+}
+
+/// Growth rings around the y axis, made irregular by turbulence
+pub struct Wood {
+    noise: Perlin,
+    /// Rings per unit distance from the axis
+    scale: f64,
+    octaves: u32,
+    /// How far turbulence warps the rings, in rings
+    distortion: f64,
+    ramp: ColorRamp,
+}
+
+impl Wood {
+    pub fn new(scale: f64) -> Self {
+        Self {
+            noise: Perlin::default(),
+            scale,
+            octaves: 3,
+            distortion: 0.4,
+            ramp: ColorRamp::new(vec![
+                (0.0, DVec3::new(0.55, 0.33, 0.16)),
+                (0.7, DVec3::new(0.45, 0.25, 0.1)),
+                (1.0, DVec3::new(0.25, 0.12, 0.05)),
+            ]),
+        }
+    }
+
+    pub fn octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    pub fn distortion(mut self, distortion: f64) -> Self {
+        self.distortion = distortion;
+        self
+    }
+
+    pub fn ramp(mut self, ramp: ColorRamp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+}
+
+impl Texture for Wood {
+    fn value(&self, _u: f64, _v: f64, point: DVec3) -> DVec3 {
+        let radius = point.x.hypot(point.z) * self.scale;
+        // Grain stretched along the axis
+        let grain = point * DVec3::new(self.scale, 0.2 * self.scale, self.scale);
+        let rings = radius + self.distortion * self.noise.turbulence(grain, self.octaves);
+        self.ramp.sample(rings.fract())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn noise_stays_in_range_and_uses_it() {
+        let perlin = Perlin::default();
+        let mut rng = rand::rng();
+        let values: Vec<f64> = (0..20_000)
+            .map(|_| perlin.noise(DVec3::new(rng.random(), rng.random(), rng.random()) * 50.0))
+            .collect();
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        assert!(min >= -1.0 && max <= 1.0, "{min} to {max}");
+        assert!(min < -0.4 && max > 0.4, "{min} to {max}");
+
+        // Zero on the lattice, where every gradient is dotted with zero
+        assert_eq!(perlin.noise(DVec3::new(3.0, -7.0, 12.0)), 0.0);
+    }
+
+    #[test]
+    fn textures_stay_within_their_ramp() {
+        let noise = NoiseTexture::new(4.0);
+        let turbulence = Turbulence::new(4.0);
+        let mut rng = rand::rng();
+        for _ in 0..5_000 {
+            let point = DVec3::new(rng.random(), rng.random(), rng.random()) * 10.0;
+            let value = noise.value(0.0, 0.0, point).x;
+            assert!(value > 0.0 && value < 1.0, "{value}");
+            let value = turbulence.value(0.0, 0.0, point).x;
+            assert!((0.0..=1.0).contains(&value), "{value}");
+        }
+    }
+}
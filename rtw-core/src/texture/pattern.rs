@@ -0,0 +1,215 @@
+use glam::{DVec2, DVec3};
+use std::sync::Arc;
+
+use crate::texture::{Texture, ramp::ColorRamp, solid::SolidColor};
+
+/// Position within the repeating tile of `scale` containing (u, v), in
+/// [0, 1) along both axes
+fn tile(u: f64, v: f64, scale: f64) -> DVec2 {
+    (DVec2::new(u, v) / scale).fract_gl()
+}
+
+/// Parallel bands of two textures across (u, v), one period of `scale`
+/// holding one band of each
+pub struct Stripes {
+    scale: f64,
+    /// Direction the bands run across, in radians from the u axis
+    angle: f64,
+    /// Fraction of each period taken by `a`
+    width: f64,
+    a: Arc<dyn Texture>,
+    b: Arc<dyn Texture>,
+}
+
+impl Stripes {
+    /// Bands alternating along u
+    pub fn new(scale: f64, a: Arc<dyn Texture>, b: Arc<dyn Texture>) -> Self {
+        Self {
+            scale,
+            angle: 0.0,
+            width: 0.5,
+            a,
+            b,
+        }
+    }
+
+    pub fn from_colors(scale: f64, a: DVec3, b: DVec3) -> Self {
+        Self::new(
+            scale,
+            Arc::new(SolidColor::new(a)),
+            Arc::new(SolidColor::new(b)),
+        )
+    }
+
+    pub fn angle(mut self, angle: f64) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    pub fn width(mut self, width: f64) -> Self {
+        self.width = width.clamp(0.0, 1.0);
+        self
+    }
+}
+
+impl Texture for Stripes {
+    fn value(&self, u: f64, v: f64, point: DVec3) -> DVec3 {
+        let across = u * self.angle.cos() + v * self.angle.sin();
+        if (across / self.scale).rem_euclid(1.0) < self.width {
+            self.a.value(u, v, point)
+        } else {
+            self.b.value(u, v, point)
+        }
+    }
+}
+
+/// Lines along u and v every `scale`, such as tiles and grout or graph
+/// paper
+pub struct Grid {
+    scale: f64,
+    /// Thickness of the lines as a fraction of a cell
+    line_width: f64,
+    line: Arc<dyn Texture>,
+    fill: Arc<dyn Texture>,
+}
+
+impl Grid {
+    pub fn new(scale: f64, line: Arc<dyn Texture>, fill: Arc<dyn Texture>) -> Self {
+        Self {
+            scale,
+            line_width: 0.05,
+            line,
+            fill,
+        }
+    }
+
+    pub fn from_colors(scale: f64, line: DVec3, fill: DVec3) -> Self {
+        Self::new(
+            scale,
+            Arc::new(SolidColor::new(line)),
+            Arc::new(SolidColor::new(fill)),
+        )
+    }
+
+    pub fn line_width(mut self, line_width: f64) -> Self {
+        self.line_width = line_width.clamp(0.0, 1.0);
+        self
+    }
+}
+
+impl Texture for Grid {
+    fn value(&self, u: f64, v: f64, point: DVec3) -> DVec3 {
+        // Lines are centered on the cell edges
+        let p = tile(u, v, self.scale);
+        let half = 0.5 * self.line_width;
+        let on_line = |x: f64| x < half || x > 1.0 - half;
+        if on_line(p.x) || on_line(p.y) {
+            self.line.value(u, v, point)
+        } else {
+            self.fill.value(u, v, point)
+        }
+    }
+}
+
+/// Round dots centered in cells of `scale` over (u, v), polka dots
+pub struct Dots {
+    scale: f64,
+    /// Radius of the dots as a fraction of a cell
+    radius: f64,
+    /// Shift every other row by half a cell
+    staggered: bool,
+    dot: Arc<dyn Texture>,
+    background: Arc<dyn Texture>,
+}
+
+impl Dots {
+    pub fn new(scale: f64, dot: Arc<dyn Texture>, background: Arc<dyn Texture>) -> Self {
+        Self {
+            scale,
+            radius: 0.3,
+            staggered: false,
+            dot,
+            background,
+        }
+    }
+
+    pub fn from_colors(scale: f64, dot: DVec3, background: DVec3) -> Self {
+        Self::new(
+            scale,
+            Arc::new(SolidColor::new(dot)),
+            Arc::new(SolidColor::new(background)),
+        )
+    }
+
+    pub fn radius(mut self, radius: f64) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn staggered(mut self) -> Self {
+        self.staggered = true;
+        self
+    }
+}
+
+impl Texture for Dots {
+    fn value(&self, u: f64, v: f64, point: DVec3) -> DVec3 {
+        let row = (v / self.scale).floor();
+        let shift = if self.staggered && row.rem_euclid(2.0) == 1.0 {
+            0.5 * self.scale
+        } else {
+            0.0
+        };
+        let p = tile(u + shift, v, self.scale);
+        if p.distance(DVec2::splat(0.5)) < self.radius {
+            self.dot.value(u, v, point)
+        } else {
+            self.background.value(u, v, point)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum GradientShape {
+    /// Along a direction at an angle in radians from the u axis, from 0 at
+    /// (0, 0) to 1 one scale further along it
+    Linear(f64),
+    /// From 0 at the center of (u, v) out to 1 half a scale away, the
+    /// middle of the edges for a scale of 1
+    Radial,
+}
+
+/// Color ramp spread over (u, v), running its full length over `scale`
+pub struct Gradient {
+    scale: f64,
+    shape: GradientShape,
+    ramp: ColorRamp,
+}
+
+impl Gradient {
+    pub fn linear(scale: f64, angle: f64, ramp: ColorRamp) -> Self {
+        Self {
+            scale,
+            shape: GradientShape::Linear(angle),
+            ramp,
+        }
+    }
+
+    pub fn radial(scale: f64, ramp: ColorRamp) -> Self {
+        Self {
+            scale,
+            shape: GradientShape::Radial,
+            ramp,
+        }
+    }
+}
+
+impl Texture for Gradient {
+    fn value(&self, u: f64, v: f64, _point: DVec3) -> DVec3 {
+        let t = match self.shape {
+            GradientShape::Linear(angle) => u * angle.cos() + v * angle.sin(),
+            GradientShape::Radial => 2.0 * DVec2::new(u, v).distance(DVec2::splat(0.5)),
+        };
+        self.ramp.sample(t / self.scale)
+    }
+}
@@ -0,0 +1,31 @@
+use glam::DVec3;
+
+/// Colors at increasing positions in [0, 1], blended linearly in between
+/// and held past the first and last one
+#[derive(Debug, Clone)]
+pub struct ColorRamp {
+    stops: Vec<(f64, DVec3)>,
+}
+
+impl ColorRamp {
+    /// Stops don't need to be sorted
+    pub fn new(mut stops: Vec<(f64, DVec3)>) -> Self {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    /// Straight blend from `a` at 0 to `b` at 1
+    pub fn between(a: DVec3, b: DVec3) -> Self {
+        Self::new(vec![(0.0, a), (1.0, b)])
+    }
+
+    pub fn sample(&self, t: f64) -> DVec3 {
+        let next = self.stops.partition_point(|(position, _)| *position <= t);
+        let before = next.checked_sub(1).map(|i| self.stops[i]);
+        match (before, self.stops.get(next)) {
+            (Some((p0, c0)), Some(&(p1, c1))) => c0.lerp(c1, (t - p0) / (p1 - p0)),
+            (Some((_, color)), None) | (None, Some(&(_, color))) => color,
+            (None, None) => DVec3::ZERO,
+        }
+    }
+}
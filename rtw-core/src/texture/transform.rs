@@ -0,0 +1,178 @@
+use glam::{DAffine2, DAffine3, DQuat, DVec2, DVec3};
+use std::sync::Arc;
+
+use crate::texture::{Footprint, Texture};
+
+/// Texture looked up at transformed (u, v) coordinates, to tile, turn or
+/// shift it over a surface. Coordinates are scaled, then rotated about the
+/// origin, then offset, so a scale of 4 repeats a texture four times.
+pub struct UvTransform {
+    texture: Arc<dyn Texture>,
+    scale: DVec2,
+    /// Counterclockwise, in radians
+    rotation: f64,
+    offset: DVec2,
+    transform: DAffine2,
+}
+
+impl UvTransform {
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self {
+            texture,
+            scale: DVec2::ONE,
+            rotation: 0.0,
+            offset: DVec2::ZERO,
+            transform: DAffine2::IDENTITY,
+        }
+    }
+
+    pub fn scale(mut self, scale: DVec2) -> Self {
+        self.scale = scale;
+        self.update()
+    }
+
+    pub fn rotation(mut self, rotation: f64) -> Self {
+        self.rotation = rotation;
+        self.update()
+    }
+
+    pub fn offset(mut self, offset: DVec2) -> Self {
+        self.offset = offset;
+        self.update()
+    }
+
+    fn update(mut self) -> Self {
+        self.transform =
+            DAffine2::from_scale_angle_translation(self.scale, self.rotation, self.offset);
+        self
+    }
+}
+
+impl Texture for UvTransform {
+    fn value(&self, u: f64, v: f64, point: DVec3) -> DVec3 {
+        let uv = self.transform.transform_point2(DVec2::new(u, v));
+        self.texture.value(uv.x, uv.y, point)
+    }
+
+    fn filtered(&self, u: f64, v: f64, point: DVec3, footprint: &Footprint) -> DVec3 {
+        let uv = self.transform.transform_point2(DVec2::new(u, v));
+        let dx = self
+            .transform
+            .transform_vector2(DVec2::new(footprint.dudx, footprint.dvdx));
+        let dy = self
+            .transform
+            .transform_vector2(DVec2::new(footprint.dudy, footprint.dvdy));
+        let footprint = Footprint {
+            dudx: dx.x,
+            dvdx: dx.y,
+            dudy: dy.x,
+            dvdy: dy.y,
+            ..*footprint
+        };
+        self.texture.filtered(uv.x, uv.y, point, &footprint)
+    }
+}
+
+/// Solid texture such as [`Checker`](crate::texture::checker::Checker)
+/// looked up at transformed points. Points are scaled, then rotated, then
+/// translated, so a scale of 2 packs twice as many features along an axis.
+pub struct SolidTransform {
+    texture: Arc<dyn Texture>,
+    scale: DVec3,
+    rotation: DQuat,
+    translation: DVec3,
+    transform: DAffine3,
+}
+
+impl SolidTransform {
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self {
+            texture,
+            scale: DVec3::ONE,
+            rotation: DQuat::IDENTITY,
+            translation: DVec3::ZERO,
+            transform: DAffine3::IDENTITY,
+        }
+    }
+
+    pub fn scale(mut self, scale: DVec3) -> Self {
+        self.scale = scale;
+        self.update()
+    }
+
+    pub fn rotation(mut self, rotation: DQuat) -> Self {
+        self.rotation = rotation;
+        self.update()
+    }
+
+    pub fn translation(mut self, translation: DVec3) -> Self {
+        self.translation = translation;
+        self.update()
+    }
+
+    fn update(mut self) -> Self {
+        self.transform =
+            DAffine3::from_scale_rotation_translation(self.scale, self.rotation, self.translation);
+        self
+    }
+}
+
+impl Texture for SolidTransform {
+    fn value(&self, u: f64, v: f64, point: DVec3) -> DVec3 {
+        self.texture
+            .value(u, v, self.transform.transform_point3(point))
+    }
+
+    fn filtered(&self, u: f64, v: f64, point: DVec3, footprint: &Footprint) -> DVec3 {
+        let footprint = Footprint {
+            dpdx: self.transform.transform_vector3(footprint.dpdx),
+            dpdy: self.transform.transform_vector3(footprint.dpdy),
+            ..*footprint
+        };
+        self.texture
+            .filtered(u, v, self.transform.transform_point3(point), &footprint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    /// Shows the coordinates it is looked up at
+    struct Uv;
+
+    impl Texture for Uv {
+        fn value(&self, u: f64, v: f64, _point: DVec3) -> DVec3 {
+            DVec3::new(u, v, 0.0)
+        }
+    }
+
+    #[test]
+    fn uv_is_scaled_then_rotated_then_offset() {
+        let texture = UvTransform::new(Arc::new(Uv))
+            .scale(DVec2::new(2.0, 3.0))
+            .rotation(FRAC_PI_2)
+            .offset(DVec2::new(0.5, 0.0));
+        let lookup = |u, v| texture.value(u, v, DVec3::ZERO);
+        assert!(lookup(1.0, 0.0).distance(DVec3::new(0.5, 2.0, 0.0)) < 1e-12);
+        assert!(lookup(0.0, 1.0).distance(DVec3::new(-2.5, 0.0, 0.0)) < 1e-12);
+    }
+
+    #[test]
+    fn inverse_uv_transform_round_trips() {
+        let (scale, rotation, offset) = (2.5, 0.7, DVec2::new(0.3, -1.2));
+        let inverse = UvTransform::new(Arc::new(Uv))
+            .scale(DVec2::splat(1.0 / scale))
+            .rotation(-rotation)
+            .offset(-DVec2::from_angle(-rotation).rotate(offset) / scale);
+        let texture = UvTransform::new(Arc::new(inverse))
+            .scale(DVec2::splat(scale))
+            .rotation(rotation)
+            .offset(offset);
+        for (u, v) in [(0.0, 0.0), (0.25, 0.8), (-3.0, 1.5)] {
+            let value = texture.value(u, v, DVec3::ZERO);
+            assert!(value.distance(DVec3::new(u, v, 0.0)) < 1e-12, "{value}");
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use glam::DVec3;
+use std::sync::Arc;
+
+use crate::{
+    hittable::HitRecord,
+    texture::{Footprint, Texture},
+};
+
+/// Axes of the point giving (u, v) for the projections along x, y and z
+const PLANES: [(usize, usize); 3] = [(2, 1), (0, 2), (0, 1)];
+
+/// Texture projected onto a surface along the x, y and z axes, blended by
+/// how squarely the surface faces each of them. For objects without usable
+/// (u, v) coordinates, such as signed distance fields or scanned meshes.
+///
+/// The wrapped texture sees world coordinates over `scale` as (u, v), so
+/// images should repeat. The blend needs the surface normal, which only a
+/// hit gives, so without one the three projections are averaged.
+pub struct Triplanar {
+    texture: Arc<dyn Texture>,
+    inv_scale: f64,
+    /// How fast the blend narrows towards the closest axis, higher values
+    /// giving shorter seams
+    sharpness: f64,
+}
+
+impl Triplanar {
+    pub fn new(texture: Arc<dyn Texture>, scale: f64) -> Self {
+        Self {
+            texture,
+            inv_scale: scale.recip(),
+            sharpness: 4.0,
+        }
+    }
+
+    pub fn sharpness(mut self, sharpness: f64) -> Self {
+        self.sharpness = sharpness;
+        self
+    }
+
+    /// Projections weighted along each axis
+    fn blend(&self, weights: DVec3, point: DVec3, footprint: Option<&Footprint>) -> DVec3 {
+        let scale = self.inv_scale;
+        PLANES
+            .iter()
+            .enumerate()
+            .filter(|(axis, _)| weights[*axis] > 0.0)
+            .map(|(axis, &(a, b))| {
+                let (u, v) = (scale * point[a], scale * point[b]);
+                let color = match footprint {
+                    Some(footprint) => {
+                        let projected = Footprint {
+                            dudx: scale * footprint.dpdx[a],
+                            dudy: scale * footprint.dpdy[a],
+                            dvdx: scale * footprint.dpdx[b],
+                            dvdy: scale * footprint.dpdy[b],
+                            ..*footprint
+                        };
+                        self.texture.filtered(u, v, point, &projected)
+                    }
+                    None => self.texture.value(u, v, point),
+                };
+                weights[axis] * color
+            })
+            .sum()
+    }
+}
+
+impl Texture for Triplanar {
+    fn value(&self, _u: f64, _v: f64, point: DVec3) -> DVec3 {
+        self.blend(DVec3::splat(1.0 / 3.0), point, None)
+    }
+
+    fn filtered(&self, _u: f64, _v: f64, point: DVec3, footprint: &Footprint) -> DVec3 {
+        self.blend(DVec3::splat(1.0 / 3.0), point, Some(footprint))
+    }
+
+    fn value_at(&self, hr: &HitRecord) -> DVec3 {
+        let weights = hr.normal.abs().powf(self.sharpness);
+        self.blend(
+            weights / weights.element_sum(),
+            hr.point,
+            hr.footprint.as_ref(),
+        )
+    }
+}
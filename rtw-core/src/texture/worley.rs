@@ -0,0 +1,129 @@
+use glam::{DVec2, DVec3};
+
+use crate::texture::{Texture, ramp::ColorRamp};
+
+/// What a [`Worley`] texture shows of its cells
+#[derive(Debug, Clone, Copy)]
+pub enum Cellular {
+    /// Distance to the nearest feature point, dark spots in bright cells
+    Distance,
+    /// Distance to the second nearest point minus the nearest, dark cracks
+    /// along the borders between cells
+    Edges,
+    /// Flat random color for each cell, Voronoi tiles like cobblestones
+    Cells,
+}
+
+/// Worley cellular noise over (u, v), from Worley 1996 "A Cellular Texture
+/// Basis Function". Each cell of `scale` holds one feature point at a
+/// random spot, and every point on the surface belongs to the closest one.
+pub struct Worley {
+    scale: f64,
+    /// How far feature points stray from the center of their cell, 0 gives
+    /// a regular grid and 1 fully random points
+    jitter: f64,
+    output: Cellular,
+    ramp: ColorRamp,
+}
+
+impl Worley {
+    pub fn new(scale: f64, output: Cellular, ramp: ColorRamp) -> Self {
+        Self {
+            scale,
+            jitter: 1.0,
+            output,
+            ramp,
+        }
+    }
+
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Feature point of a cell, in cell units
+    fn feature(&self, cell: DVec2) -> DVec2 {
+        let offset = DVec2::new(hash(cell, 0), hash(cell, 1));
+        cell + 0.5 + self.jitter * (offset - 0.5)
+    }
+}
+
+/// Pseudo random value in [0, 1) fixed for each cell and `channel`
+fn hash(cell: DVec2, channel: u32) -> f64 {
+    let mut h = (cell.x as i64 as u32)
+        .wrapping_mul(0x8da6_b343)
+        .wrapping_add((cell.y as i64 as u32).wrapping_mul(0xd816_3841))
+        .wrapping_add(channel.wrapping_mul(0xcb1a_b31f));
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^= h >> 16;
+    h as f64 / (u32::MAX as f64 + 1.0)
+}
+
+impl Worley {
+    /// Distances from (u, v) to the nearest and second nearest feature
+    /// points in cell units, and the cell of the nearest one
+    fn nearest(&self, u: f64, v: f64) -> (f64, f64, DVec2) {
+        let p = DVec2::new(u, v) / self.scale;
+        let home = p.floor();
+
+        // The nearest point is always within the neighbouring cells, but with
+        // a large jitter the second nearest can sit one cell further out
+        let (mut nearest, mut second) = (f64::INFINITY, f64::INFINITY);
+        let mut nearest_cell = home;
+        for (dx, dy) in (0..25).map(|i| (i % 5 - 2, i / 5 - 2)) {
+            let cell = home + DVec2::new(dx as f64, dy as f64);
+            let distance = p.distance(self.feature(cell));
+            if distance < nearest {
+                (second, nearest, nearest_cell) = (nearest, distance, cell);
+            } else if distance < second {
+                second = distance;
+            }
+        }
+        (nearest, second, nearest_cell)
+    }
+}
+
+impl Texture for Worley {
+    fn value(&self, u: f64, v: f64, _point: DVec3) -> DVec3 {
+        let (nearest, second, nearest_cell) = self.nearest(u, v);
+        let t = match self.output {
+            Cellular::Distance => nearest,
+            Cellular::Edges => second - nearest,
+            Cellular::Cells => hash(nearest_cell, 2),
+        };
+        self.ramp.sample(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn searched_cells_hold_the_two_nearest_points() {
+        let worley = Worley::new(
+            0.25,
+            Cellular::Edges,
+            ColorRamp::between(DVec3::ZERO, DVec3::ONE),
+        );
+        let mut rng = rand::rng();
+        for _ in 0..2_000 {
+            let (u, v) = (rng.random_range(-4.0..4.0), rng.random_range(-4.0..4.0));
+            let (nearest, second, _) = worley.nearest(u, v);
+            assert!(nearest <= second);
+
+            // Every feature point in a much wider neighbourhood
+            let p = DVec2::new(u, v) / worley.scale;
+            let mut distances: Vec<f64> = (0..121)
+                .map(|i| p.floor() + DVec2::new((i % 11 - 5) as f64, (i / 11 - 5) as f64))
+                .map(|cell| p.distance(worley.feature(cell)))
+                .collect();
+            distances.sort_by(f64::total_cmp);
+            assert_eq!((nearest, second), (distances[0], distances[1]));
+        }
+    }
+}
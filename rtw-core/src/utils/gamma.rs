@@ -1,3 +1,7 @@
 pub fn linear_to_gamma(linear_component: f64) -> f64 {
     linear_component.sqrt()
 }
+
+pub fn gamma_to_linear(gamma_component: f64) -> f64 {
+    gamma_component * gamma_component
+}
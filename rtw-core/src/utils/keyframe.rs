@@ -0,0 +1,195 @@
+use glam::{DQuat, DVec3};
+use std::ops::{Add, Mul, Sub};
+
+use crate::{Result, RtwError};
+
+/// How values are blended between two keyframes
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Interpolation {
+    /// Straight line between the two surrounding keyframes
+    #[default]
+    Linear,
+    /// Smooth curve through all keyframes, using the neighbouring keyframes
+    /// to shape the tangents
+    CatmullRom,
+}
+
+/// Values that can be blended between two keyframes
+pub trait Interpolate: Copy {
+    /// Blend from `a` at `t = 0` to `b` at `t = 1`
+    fn interpolate(a: Self, b: Self, t: f64) -> Self;
+
+    /// Catmull-Rom segment from `p1` at `t = 0` to `p2` at `t = 1`, with
+    /// `p0` and `p3` the keyframes before and after the segment. Values
+    /// without a meaningful spline blend linearly.
+    fn spline(_p0: Self, p1: Self, p2: Self, _p3: Self, t: f64) -> Self {
+        Self::interpolate(p1, p2, t)
+    }
+}
+
+fn catmull_rom<T>(p0: T, p1: T, p2: T, p3: T, t: f64) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f64, Output = T>,
+{
+    let (t2, t3) = (t * t, t * t * t);
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+impl Interpolate for f64 {
+    fn interpolate(a: Self, b: Self, t: f64) -> Self {
+        a + (b - a) * t
+    }
+
+    fn spline(p0: Self, p1: Self, p2: Self, p3: Self, t: f64) -> Self {
+        catmull_rom(p0, p1, p2, p3, t)
+    }
+}
+
+impl Interpolate for DVec3 {
+    fn interpolate(a: Self, b: Self, t: f64) -> Self {
+        a.lerp(b, t)
+    }
+
+    fn spline(p0: Self, p1: Self, p2: Self, p3: Self, t: f64) -> Self {
+        catmull_rom(p0, p1, p2, p3, t)
+    }
+}
+
+impl Interpolate for DQuat {
+    fn interpolate(a: Self, b: Self, t: f64) -> Self {
+        a.slerp(b, t)
+    }
+}
+
+/// Values at increasing points in time.
+///
+/// Sampling between two keyframes blends them with the chosen
+/// [`Interpolation`], linear by default. Sampling before the first or after
+/// the last keyframe holds the value of that keyframe, so an object is at
+/// rest outside of its animated range.
+#[derive(Debug, Clone)]
+pub struct Keyframes<T> {
+    keys: Vec<(f64, T)>,
+    interpolation: Interpolation,
+}
+
+impl<T> Keyframes<T>
+where
+    T: Interpolate,
+{
+    pub fn new(mut keys: Vec<(f64, T)>) -> Result<Self> {
+        if keys.is_empty() {
+            return Err(RtwError::NoKeyframes);
+        }
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Self {
+            keys,
+            interpolation: Interpolation::default(),
+        })
+    }
+
+    pub fn constant(value: T) -> Self {
+        Self {
+            keys: vec![(0.0, value)],
+            interpolation: Interpolation::default(),
+        }
+    }
+
+    pub fn interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn times(&self) -> impl Iterator<Item = f64> + '_ {
+        self.keys.iter().map(|(time, _)| *time)
+    }
+
+    pub fn first(&self) -> T {
+        self.keys[0].1
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.keys.len() == 1
+    }
+
+    pub fn sample(&self, time: f64) -> T {
+        // Index of the first keyframe strictly after `time`
+        let next = self.keys.partition_point(|(t, _)| *t <= time);
+        if next == 0 {
+            return self.keys[0].1;
+        }
+        if next == self.keys.len() {
+            return self.keys[next - 1].1;
+        }
+
+        let (t0, v0) = self.keys[next - 1];
+        let (t1, v1) = self.keys[next];
+        let t = (time - t0) / (t1 - t0);
+        match self.interpolation {
+            Interpolation::Linear => T::interpolate(v0, v1, t),
+            Interpolation::CatmullRom => {
+                // The end keyframes are repeated to close off the curve
+                let before = self.keys[next.saturating_sub(2)].1;
+                let after = self.keys[(next + 1).min(self.keys.len() - 1)].1;
+                T::spline(before, v0, v1, after, t)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outside_the_keyframes_values_hold() {
+        let keys = Keyframes::new(vec![(2.0, 5.0), (1.0, 3.0)]).unwrap();
+        assert_eq!(keys.sample(0.0), 3.0);
+        assert_eq!(keys.sample(1.0), 3.0);
+        assert_eq!(keys.sample(2.0), 5.0);
+        assert_eq!(keys.sample(7.0), 5.0);
+        assert_eq!(keys.sample(1.25), 3.5);
+    }
+
+    #[test]
+    fn one_keyframe_is_static() {
+        let keys = Keyframes::constant(DVec3::ONE);
+        assert!(keys.is_static());
+        assert_eq!(keys.sample(-1.0), DVec3::ONE);
+        assert_eq!(keys.sample(1.0), DVec3::ONE);
+        assert!(Keyframes::<f64>::new(vec![]).is_err());
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_every_keyframe() {
+        let keys = Keyframes::new(vec![(0.0, 0.0), (1.0, 2.0), (2.0, 1.0), (3.0, 4.0)])
+            .unwrap()
+            .interpolation(Interpolation::CatmullRom);
+        for (time, value) in [(0.0, 0.0), (1.0, 2.0), (2.0, 1.0), (3.0, 4.0)] {
+            assert!((keys.sample(time) - value).abs() < 1e-12);
+        }
+        // Tangents come from the neighbours and are the same on both sides
+        // of a keyframe
+        let h = 1e-6;
+        let before = (keys.sample(1.0) - keys.sample(1.0 - h)) / h;
+        let after = (keys.sample(1.0 + h) - keys.sample(1.0)) / h;
+        assert!((before - 0.5).abs() < 1e-4 && (after - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn catmull_rom_keeps_evenly_spaced_points_on_a_line() {
+        let points = (0..4).map(|i| (i as f64, DVec3::new(i as f64, 2.0 * i as f64, 1.0)));
+        let keys = Keyframes::new(points.collect())
+            .unwrap()
+            .interpolation(Interpolation::CatmullRom);
+        // The ends repeat the end keyframes, which bends the outer segments
+        for time in [1.2, 1.5, 1.8] {
+            let expected = DVec3::new(time, 2.0 * time, 1.0);
+            assert!(keys.sample(time).distance(expected) < 1e-12);
+        }
+    }
+}
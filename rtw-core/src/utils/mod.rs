@@ -1,3 +1,6 @@
 pub(crate) mod vec;
 pub(crate) mod gamma;
 pub(crate) mod interval;
+pub(crate) mod keyframe;
+pub(crate) mod onb;
+pub(crate) mod perlin;
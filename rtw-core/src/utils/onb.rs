@@ -0,0 +1,42 @@
+use glam::DVec3;
+
+/// Orthonormal basis with `w` along a given direction
+#[derive(Debug, Clone, Copy)]
+pub struct Onb {
+    pub u: DVec3,
+    pub v: DVec3,
+    pub w: DVec3,
+}
+
+impl Onb {
+    pub fn new(n: DVec3) -> Self {
+        let w = n.normalize();
+        let a = if w.x.abs() > 0.9 { DVec3::Y } else { DVec3::X };
+        let v = w.cross(a).normalize();
+        let u = w.cross(v);
+        Self { u, v, w }
+    }
+
+    /// Basis with `w` along the normal and `u` along the tangent, made
+    /// perpendicular to it. Falls back to [`Onb::new`] when the tangent
+    /// runs along the normal.
+    pub fn from_tangent(n: DVec3, tangent: DVec3) -> Self {
+        let w = n.normalize();
+        let u = (tangent - tangent.dot(w) * w).normalize_or_zero();
+        if u == DVec3::ZERO {
+            return Self::new(n);
+        }
+        let v = w.cross(u);
+        Self { u, v, w }
+    }
+
+    /// Converts world space coordinates to this basis
+    pub fn local(&self, world: DVec3) -> DVec3 {
+        DVec3::new(world.dot(self.u), world.dot(self.v), world.dot(self.w))
+    }
+
+    /// Converts local coordinates to world space
+    pub fn transform(&self, local: DVec3) -> DVec3 {
+        local.x * self.u + local.y * self.v + local.z * self.w
+    }
+}
@@ -0,0 +1,66 @@
+use glam::DVec3;
+use rand::seq::SliceRandom;
+
+use crate::utils::vec::random_unit_vector;
+
+const POINT_COUNT: usize = 256;
+
+/// Ken Perlin's gradient noise, smooth pseudo random values in about [-1, 1]
+/// that vary on the scale of a unit cell
+pub struct Perlin {
+    gradients: [DVec3; POINT_COUNT],
+    perm_x: [usize; POINT_COUNT],
+    perm_y: [usize; POINT_COUNT],
+    perm_z: [usize; POINT_COUNT],
+}
+
+impl Default for Perlin {
+    fn default() -> Self {
+        Self {
+            gradients: std::array::from_fn(|_| random_unit_vector()),
+            perm_x: permutation(),
+            perm_y: permutation(),
+            perm_z: permutation(),
+        }
+    }
+}
+
+fn permutation() -> [usize; POINT_COUNT] {
+    let mut perm = std::array::from_fn(|i| i);
+    perm.shuffle(&mut rand::rng());
+    perm
+}
+
+impl Perlin {
+    pub fn noise(&self, point: DVec3) -> f64 {
+        let cell = point.floor();
+        let f = point - cell;
+        // Hermite smoothing hides the grid
+        let w = f * f * (3.0 - 2.0 * f);
+
+        let mut sum = 0.0;
+        for (di, dj, dk) in (0..8).map(|c| (c & 1, (c >> 1) & 1, (c >> 2) & 1)) {
+            let gradient = self.gradients[self.perm_x[(cell.x as i64 + di) as usize & 255]
+                ^ self.perm_y[(cell.y as i64 + dj) as usize & 255]
+                ^ self.perm_z[(cell.z as i64 + dk) as usize & 255]];
+            let corner = DVec3::new(di as f64, dj as f64, dk as f64);
+            let weight = DVec3::select(corner.cmpeq(DVec3::ONE), w, 1.0 - w);
+            sum += weight.element_product() * gradient.dot(f - corner);
+        }
+        sum
+    }
+
+    /// Sum of `octaves` layers of noise magnitude, each at twice the
+    /// frequency and half the weight of the previous one
+    pub fn turbulence(&self, point: DVec3, octaves: u32) -> f64 {
+        let mut sum = 0.0;
+        let mut point = point;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            sum += weight * self.noise(point).abs();
+            weight *= 0.5;
+            point *= 2.0;
+        }
+        sum
+    }
+}
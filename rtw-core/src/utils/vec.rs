@@ -50,3 +50,17 @@ pub(crate) fn random_in_unit_disk() -> DVec3 {
         }
     }
 }
+
+/// Direction on the +z hemisphere with density cos(theta) / pi
+pub(crate) fn random_cosine_direction() -> DVec3 {
+    let mut rng = rand::rng();
+    let r1: f64 = rng.random();
+    let r2: f64 = rng.random();
+
+    let phi = 2.0 * std::f64::consts::PI * r1;
+    let x = phi.cos() * r2.sqrt();
+    let y = phi.sin() * r2.sqrt();
+    let z = (1.0 - r2).sqrt();
+
+    DVec3::new(x, y, z)
+}